@@ -0,0 +1,68 @@
+#![feature(test)]
+extern crate grep;
+extern crate test;
+
+use grep::{grep, Flags};
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+use test::Bencher;
+
+#[bench]
+fn bench_few_files_parallel(b: &mut Bencher) {
+    let files = corpus("few", 4, 200);
+    let files: Vec<&str> = files.iter().map(String::as_str).collect();
+    let flags = Flags::new(&["-n", "-j", "0"]);
+    b.iter(|| grep("Agamemnon", &flags, &files).unwrap());
+}
+
+#[bench]
+fn bench_few_files_serial(b: &mut Bencher) {
+    let files = corpus("few", 4, 200);
+    let files: Vec<&str> = files.iter().map(String::as_str).collect();
+    let flags = Flags::new(&["-n"]);
+    b.iter(|| grep("Agamemnon", &flags, &files).unwrap());
+}
+
+#[bench]
+fn bench_many_files_parallel(b: &mut Bencher) {
+    let files = corpus("many", 2000, 20);
+    let files: Vec<&str> = files.iter().map(String::as_str).collect();
+    let flags = Flags::new(&["-n", "-j", "0"]);
+    b.iter(|| grep("Agamemnon", &flags, &files).unwrap());
+}
+
+#[bench]
+fn bench_many_files_serial(b: &mut Bencher) {
+    let files = corpus("many", 2000, 20);
+    let files: Vec<&str> = files.iter().map(String::as_str).collect();
+    let flags = Flags::new(&["-n"]);
+    b.iter(|| grep("Agamemnon", &flags, &files).unwrap());
+}
+
+/// Writes `count` files, each repeating the text `repeat` times, unless a previous run already did.
+fn corpus(name: &str, count: usize, repeat: usize) -> Vec<String> {
+    let dir: PathBuf = env::temp_dir().join(format!("grep_benchmark_{}", name));
+    fs::create_dir_all(&dir).unwrap();
+    let content = ILIAD.repeat(repeat);
+    (0..count)
+        .map(|i| {
+            let path = dir.join(format!("iliad_{}.txt", i));
+            if !path.exists() {
+                fs::write(&path, &content).unwrap();
+            }
+            path.to_string_lossy().into_owned()
+        })
+        .collect()
+}
+
+const ILIAD: &str = "Achilles sing, O Goddess! Peleus' son;
+His wrath pernicious, who ten thousand woes
+Caused to Achaia's host, sent many a soul
+Illustrious into Ades premature,
+And Heroes gave (so stood the will of Jove)
+To dogs and to all ravening fowls a prey,
+When fierce dispute had separated once
+The noble Chief Achilles from the son
+Of Atreus, Agamemnon, King of men.
+";
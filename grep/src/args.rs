@@ -0,0 +1,298 @@
+//! Command-line parsing for `grep`, shared by `Flags::new` and the `grep` binary.
+
+use crate::{Color, Flags};
+use std::error;
+use std::fmt;
+use std::mem;
+
+/// A parsed command line: the flags, the patterns and the files to search. Patterns read from files
+/// with `-f` are left to the search.
+#[derive(Debug)]
+pub struct Args {
+    pub flags: Flags,
+    pub patterns: Vec<String>,
+    pub files: Vec<String>,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum ArgsError {
+    UnknownFlag(String),
+    MissingArgument(String),
+    InvalidArgument { flag: String, value: String },
+    UnexpectedArgument(String),
+    UnexpectedOperand(String),
+    MissingPattern,
+    /// Two flags that cannot be used together, by their long names.
+    ConflictingFlags(String, String),
+}
+
+impl fmt::Display for ArgsError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ArgsError::UnknownFlag(flag) => write!(f, "unrecognized option '{}'", flag),
+            ArgsError::MissingArgument(flag) => write!(f, "option '{}' requires an argument", flag),
+            ArgsError::InvalidArgument { flag, value } => {
+                write!(f, "invalid argument '{}' for '{}'", value, flag)
+            }
+            ArgsError::UnexpectedArgument(flag) => {
+                write!(f, "option '{}' doesn't allow an argument", flag)
+            }
+            ArgsError::UnexpectedOperand(operand) => write!(f, "unexpected operand '{}'", operand),
+            ArgsError::MissingPattern => write!(f, "no pattern given"),
+            ArgsError::ConflictingFlags(first, second) => {
+                write!(f, "options '{}' and '{}' cannot be combined", first, second)
+            }
+        }
+    }
+}
+
+impl error::Error for ArgsError {}
+
+impl Args {
+    /// Parses a command line, without the program name, like GNU grep: unless patterns are given
+    /// with `-e` or `-f`, the first operand is the pattern, and the other operands are files. Flags
+    /// may appear anywhere before a `--` terminator, and short flags may be combined (`-inv`,
+    /// `-nA2`).
+    pub fn parse<S: AsRef<str>>(args: &[S]) -> Result<Self, ArgsError> {
+        let args: Vec<&str> = args.iter().map(AsRef::as_ref).collect();
+        let (mut flags, mut operands) = parse(&args)?;
+        let patterns = if !flags.patterns.is_empty() || !flags.pattern_files.is_empty() {
+            mem::take(&mut flags.patterns)
+        } else if operands.is_empty() {
+            return Err(ArgsError::MissingPattern);
+        } else {
+            vec![operands.remove(0).to_string()]
+        };
+        let files = operands.into_iter().map(str::to_string).collect();
+        Ok(Args { flags, patterns, files })
+    }
+}
+
+impl Flags {
+    /// Parses flags alone, rejecting any operand.
+    pub fn parse(flags: &[&str]) -> Result<Self, ArgsError> {
+        let (flags, operands) = parse(flags)?;
+        match operands.first() {
+            Some(operand) => Err(ArgsError::UnexpectedOperand(operand.to_string())),
+            None => Ok(flags),
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Opt {
+    LineNumber,
+    FilesWithMatches,
+    IgnoreCase,
+    InvertMatch,
+    LineRegexp,
+    WordRegexp,
+    FixedStrings,
+    Recursive,
+    DereferenceRecursive,
+    AfterContext,
+    BeforeContext,
+    Context,
+    Include,
+    Exclude,
+    ExcludeDir,
+    Count,
+    OnlyMatching,
+    MaxCount,
+    FilesWithoutMatch,
+    Threads,
+    Regexp,
+    File,
+    Json,
+    Color,
+}
+
+/// Every flag, by its short and long names.
+const OPTIONS: &[(Option<char>, &str, Opt)] = &[
+    (Some('e'), "regexp", Opt::Regexp),
+    (Some('f'), "file", Opt::File),
+    (Some('n'), "line-number", Opt::LineNumber),
+    (Some('l'), "files-with-matches", Opt::FilesWithMatches),
+    (Some('L'), "files-without-match", Opt::FilesWithoutMatch),
+    (Some('c'), "count", Opt::Count),
+    (Some('o'), "only-matching", Opt::OnlyMatching),
+    (Some('m'), "max-count", Opt::MaxCount),
+    (Some('i'), "ignore-case", Opt::IgnoreCase),
+    (Some('v'), "invert-match", Opt::InvertMatch),
+    (Some('x'), "line-regexp", Opt::LineRegexp),
+    (Some('w'), "word-regexp", Opt::WordRegexp),
+    (Some('F'), "fixed-strings", Opt::FixedStrings),
+    (Some('r'), "recursive", Opt::Recursive),
+    (Some('R'), "dereference-recursive", Opt::DereferenceRecursive),
+    (Some('A'), "after-context", Opt::AfterContext),
+    (Some('B'), "before-context", Opt::BeforeContext),
+    (Some('C'), "context", Opt::Context),
+    (Some('j'), "threads", Opt::Threads),
+    (None, "include", Opt::Include),
+    (None, "exclude", Opt::Exclude),
+    (None, "exclude-dir", Opt::ExcludeDir),
+    (None, "json", Opt::Json),
+    (None, "color", Opt::Color),
+    (None, "colour", Opt::Color),
+];
+
+impl Opt {
+    fn takes_argument(self) -> bool {
+        matches!(
+            self,
+            Opt::AfterContext
+                | Opt::BeforeContext
+                | Opt::Context
+                | Opt::Include
+                | Opt::Exclude
+                | Opt::ExcludeDir
+                | Opt::MaxCount
+                | Opt::Threads
+                | Opt::Regexp
+                | Opt::File
+        )
+    }
+
+    /// The value of a flag whose argument may be left out, which can then only be given attached.
+    fn default_argument(self) -> Option<&'static str> {
+        match self {
+            Opt::Color => Some("auto"),
+            _ => None,
+        }
+    }
+}
+
+/// Flags being parsed, with context lengths kept apart until the end so that explicit `-A` and
+/// `-B` take precedence over `-C` whatever their order.
+#[derive(Default)]
+struct Parser {
+    flags: Flags,
+    after_context: Option<usize>,
+    before_context: Option<usize>,
+    context: usize,
+}
+
+impl Parser {
+    fn apply(&mut self, opt: Opt, name: &str, value: Option<&str>) -> Result<(), ArgsError> {
+        let value = value.unwrap_or_default();
+        let number = || {
+            value.parse::<usize>().map_err(|_| ArgsError::InvalidArgument {
+                flag: name.to_string(),
+                value: value.to_string(),
+            })
+        };
+        match opt {
+            Opt::LineNumber => self.flags.print_line_numbers = true,
+            Opt::FilesWithMatches => self.flags.print_file_names = true,
+            Opt::IgnoreCase => self.flags.case_insensitive = true,
+            Opt::InvertMatch => self.flags.invert = true,
+            Opt::LineRegexp => self.flags.match_entire_lines = true,
+            Opt::WordRegexp => self.flags.match_words = true,
+            Opt::FixedStrings => self.flags.fixed_strings = true,
+            Opt::Recursive => self.flags.recursive = true,
+            Opt::DereferenceRecursive => {
+                self.flags.recursive = true;
+                self.flags.dereference = true;
+            }
+            Opt::AfterContext => self.after_context = Some(number()?),
+            Opt::BeforeContext => self.before_context = Some(number()?),
+            Opt::Context => self.context = number()?,
+            Opt::Include => self.flags.include.push(value.to_string()),
+            Opt::Exclude => self.flags.exclude.push(value.to_string()),
+            Opt::ExcludeDir => self.flags.exclude_dir.push(value.to_string()),
+            Opt::Count => self.flags.count = true,
+            Opt::OnlyMatching => self.flags.only_matching = true,
+            Opt::MaxCount => self.flags.max_count = Some(number()?),
+            Opt::FilesWithoutMatch => self.flags.files_without_match = true,
+            Opt::Threads => self.flags.threads = Some(number()?),
+            Opt::Regexp => self.flags.patterns.push(value.to_string()),
+            Opt::File => self.flags.pattern_files.push(value.to_string()),
+            Opt::Json => self.flags.json = true,
+            Opt::Color => {
+                self.flags.color = match value {
+                    "always" | "yes" | "force" => Color::Always,
+                    "never" | "no" | "none" => Color::Never,
+                    "auto" | "tty" | "if-tty" => Color::Auto,
+                    _ => {
+                        return Err(ArgsError::InvalidArgument {
+                            flag: name.to_string(),
+                            value: value.to_string(),
+                        })
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Rejects `--json` along with `-c`, `-l` or `-L`, which print no lines for it to describe.
+    fn finish(mut self) -> Result<Flags, ArgsError> {
+        self.flags.after_context = self.after_context.unwrap_or(self.context);
+        self.flags.before_context = self.before_context.unwrap_or(self.context);
+        let summaries = [
+            (self.flags.count, "--count"),
+            (self.flags.print_file_names, "--files-with-matches"),
+            (self.flags.files_without_match, "--files-without-match"),
+        ];
+        match summaries.iter().find(|&&(set, _)| set) {
+            Some(&(_, flag)) if self.flags.json => {
+                Err(ArgsError::ConflictingFlags("--json".to_string(), flag.to_string()))
+            }
+            _ => Ok(self.flags),
+        }
+    }
+}
+
+/// Splits arguments into flags and operands. Flags taking an argument accept it either attached
+/// (`-A2`, `--include=*.rs`) or as the next argument (`-A 2`, `--include *.rs`).
+fn parse<'a>(args: &[&'a str]) -> Result<(Flags, Vec<&'a str>), ArgsError> {
+    let mut parser = Parser::default();
+    let mut operands = Vec::new();
+    let mut args = args.iter().copied();
+    while let Some(arg) = args.next() {
+        if arg == "--" {
+            operands.extend(&mut args);
+        } else if let Some(long) = arg.strip_prefix("--") {
+            let (name, attached) = match long.find('=') {
+                Some(i) => (&long[..i], Some(&long[i + 1..])),
+                None => (long, None),
+            };
+            let opt = OPTIONS.iter()
+                .find(|(_, long, _)| *long == name)
+                .map(|&(_, _, opt)| opt)
+                .ok_or_else(|| ArgsError::UnknownFlag(arg.to_string()))?;
+            let flag = format!("--{}", name);
+            let value = if let Some(default) = opt.default_argument() {
+                Some(attached.unwrap_or(default))
+            } else if !opt.takes_argument() {
+                if attached.is_some() {
+                    return Err(ArgsError::UnexpectedArgument(flag));
+                }
+                None
+            } else {
+                Some(attached.or_else(|| args.next()).ok_or_else(|| ArgsError::MissingArgument(flag.clone()))?)
+            };
+            parser.apply(opt, &flag, value)?;
+        } else if arg.len() > 1 && arg.starts_with('-') {
+            for (i, c) in arg.char_indices().skip(1) {
+                let opt = OPTIONS.iter()
+                    .find(|(short, _, _)| *short == Some(c))
+                    .map(|&(_, _, opt)| opt)
+                    .ok_or_else(|| ArgsError::UnknownFlag(format!("-{}", c)))?;
+                let flag = format!("-{}", c);
+                if !opt.takes_argument() {
+                    parser.apply(opt, &flag, None)?;
+                    continue;
+                }
+                let rest = &arg[i + c.len_utf8()..];
+                let value = if rest.is_empty() { args.next() } else { Some(rest) };
+                let value = value.ok_or_else(|| ArgsError::MissingArgument(flag.clone()))?;
+                parser.apply(opt, &flag, Some(value))?;
+                break;
+            }
+        } else {
+            operands.push(arg);
+        }
+    }
+    Ok((parser.finish()?, operands))
+}
@@ -1,96 +1,331 @@
-use failure::Error;
+use failure::{format_err, Error};
+use rayon::prelude::*;
+use rayon::ThreadPoolBuilder;
+use std::error;
+use std::fmt;
 use std::fs::File;
-use std::io::Read;
+use std::io::{self, BufRead, BufReader, Read};
 
-#[derive(Debug)]
+mod args;
+mod matcher;
+mod printer;
+mod search;
+mod walk;
+
+use crate::matcher::Matcher;
+use crate::printer::Printer;
+use crate::search::{SearchConfig, Searcher};
+
+pub use crate::args::{Args, ArgsError};
+pub use crate::matcher::Patterns;
+pub use crate::search::{LineKind, Match};
+
+#[derive(Clone, Debug, Default, PartialEq)]
 pub struct Flags {
     print_line_numbers: bool,
     print_file_names: bool,
     case_insensitive: bool,
     invert: bool,
     match_entire_lines: bool,
+    match_words: bool,
+    patterns: Vec<String>,
+    pattern_files: Vec<String>,
+    fixed_strings: bool,
+    after_context: usize,
+    before_context: usize,
+    recursive: bool,
+    dereference: bool,
+    include: Vec<String>,
+    exclude: Vec<String>,
+    exclude_dir: Vec<String>,
+    count: bool,
+    only_matching: bool,
+    max_count: Option<usize>,
+    files_without_match: bool,
+    threads: Option<usize>,
+    json: bool,
+    color: Color,
+}
+
+/// When to color the output with `--color`.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+enum Color {
+    #[default]
+    Never,
+    Auto,
+    Always,
 }
 
 impl Flags {
+    /// Parses the flags like `Flags::parse`, panicking on invalid ones.
     pub fn new(flags: &[&str]) -> Self {
-        let mut res = Self {
-            print_line_numbers: false,
-            print_file_names: false,
-            case_insensitive: false,
-            invert: false,
-            match_entire_lines: false,
-        };
-        for &f in flags {
-            match f {
-                "-n" => res.print_line_numbers = true,
-                "-l" => res.print_file_names = true,
-                "-i" => res.case_insensitive = true,
-                "-v" => res.invert = true,
-                "-x" => res.match_entire_lines = true,
-                _ => unreachable!()
-            }
+        Self::parse(flags).unwrap_or_else(|e| panic!("grep: {}", e))
+    }
+
+    /// Decides whether `--color=auto` colors the output, which it does only when the output goes
+    /// to a terminal. Until then `auto` leaves the output uncolored.
+    pub fn resolve_color(&mut self, is_terminal: bool) {
+        if self.color == Color::Auto {
+            self.color = if is_terminal { Color::Always } else { Color::Never };
         }
-        res
+    }
+
+    /// Whether the lines found are printed, rather than summed up per file by `-c`, `-l` or `-L`.
+    fn prints_lines(&self) -> bool {
+        !(self.count || self.print_file_names || self.files_without_match)
     }
 }
 
-pub fn grep(pattern: &str, flags: &Flags, files: &[&str]) -> Result<Vec<String>, Error> {
+/// Builds the matcher for the given patterns, those given with `-e`, and those read from the files
+/// given with `-f`, one per line.
+fn matcher<P: Patterns + ?Sized>(patterns: &P, flags: &Flags) -> Result<Matcher, Error> {
+    let mut all: Vec<String> = patterns.patterns().into_iter().map(str::to_string).collect();
+    all.extend(flags.patterns.iter().cloned());
+    for path in flags.pattern_files.iter() {
+        let mut contents = String::new();
+        open(path)
+            .and_then(|mut reader| reader.read_to_string(&mut contents))
+            .map_err(|e| format_err!("{}: {}", path, e))?;
+        all.extend(contents.lines().map(str::to_string));
+    }
+    Ok(Matcher::new(&all, flags)?)
+}
+
+/// The name under which standard input is reported, as in GNU grep.
+const STDIN_NAME: &str = "(standard input)";
+
+/// Searches the given files, or standard input when there are none (the working directory with
+/// `-r`), for lines matching any of the patterns, returning the lines to print or the first error
+/// met.
+pub fn grep<P: Patterns + ?Sized>(patterns: &P, flags: &Flags, files: &[&str]) -> Result<Vec<String>, Error> {
     let mut res: Vec<String> = Vec::new();
-    for path in files.iter() {
-        let mut string = String::new();
-        File::open(path)?.read_to_string(&mut string)?;
-
-        let matches: Vec<(usize, &str)> = string.lines()
-            .enumerate()
-            .filter(|(_, l)| {
-                if flags.case_insensitive {
-                    let s = l.to_lowercase();
-                    let p = pattern.to_lowercase();
-                    if flags.match_entire_lines {
-                        if flags.invert {
-                            s != p
-                        } else {
-                            s == p
-                        }
-                    } else {
-                        if flags.invert {
-                            !s.contains(&p)
-                        } else {
-                            s.contains(&p)
-                        }
-                    }
-                } else {
-                    if flags.match_entire_lines {
-                        if flags.invert {
-                            **l != *pattern
-                        } else {
-                            **l == *pattern
-                        }
-                    } else {
-                        if flags.invert {
-                            !l.contains(pattern)
-                        } else {
-                            l.contains(pattern)
-                        }
+    let mut first_error = None;
+    grep_each(patterns, flags, files, &mut |line| res.push(line.to_string()), &mut |_, e| {
+        first_error.get_or_insert(e);
+    })?;
+    match first_error {
+        Some(e) => Err(e),
+        None => Ok(res),
+    }
+}
+
+/// Searches like `grep`, passing each line to print to `output` as soon as it is found. Files
+/// that cannot be searched are reported to `errors` with their path, and the search goes on.
+/// A path of `-` stands for standard input.
+///
+/// Returns whether any line was selected, or an error if a pattern or a glob is invalid or a pattern
+/// file cannot be read.
+pub fn grep_each<P: Patterns + ?Sized>(
+    patterns: &P,
+    flags: &Flags,
+    files: &[&str],
+    output: &mut dyn FnMut(&str),
+    errors: &mut dyn FnMut(&str, Error),
+) -> Result<bool, Error> {
+    let matcher = matcher(patterns, flags)?;
+    let files = match files {
+        [] if flags.recursive => &["."],
+        [] => &["-"],
+        files => files,
+    };
+    let (paths, print_file_names) = walk::files(files, flags)?;
+    let config = SearchConfig::new(flags);
+    let mut printer = Printer::new(output, print_file_names, flags);
+    match flags.threads {
+        Some(threads) if paths.len() > 1 => {
+            search_parallel(paths, threads, &matcher, config, flags, &mut printer, errors)
+        }
+        _ => {
+            let mut matched = false;
+            for path in paths {
+                let path = match path {
+                    Ok(path) => path,
+                    Err(e) => {
+                        errors(&e.path, e.error);
+                        continue;
                     }
+                };
+                match search_path(&path, &matcher, config, &mut printer) {
+                    Ok(selected) => matched |= selected,
+                    Err(e) => errors(&path, e.into()),
                 }
-            }).collect();
-
-        if flags.print_file_names && matches.len() > 0 {
-            res.push(path.to_string());
-        } else {
-            matches.iter().for_each(|(i, l)| {
-                if files.len() > 1 && flags.print_line_numbers {
-                    res.push(format!("{}:{}:{}", path, i + 1, l))
-                } else if files.len() > 1 {
-                    res.push(format!("{}:{}", path, l))
-                } else if flags.print_line_numbers {
-                    res.push(format!("{}:{}", i + 1, l))
-                } else {
-                    res.push(format!("{}", l))
+            }
+            Ok(matched)
+        }
+    }
+}
+
+/// How many files each worker thread searches, on average, before their output is printed.
+const FILES_PER_THREAD: usize = 8;
+
+/// Searches files on a pool of `threads` worker threads, or one per CPU if 0. The output of each
+/// file is buffered until all the files before it are done, so that it is printed in the same
+/// order as when searching serially.
+fn search_parallel(
+    paths: Vec<Result<String, FileError>>,
+    threads: usize,
+    matcher: &Matcher,
+    config: SearchConfig,
+    flags: &Flags,
+    printer: &mut Printer,
+    errors: &mut dyn FnMut(&str, Error),
+) -> Result<bool, Error> {
+    let pool = ThreadPoolBuilder::new().num_threads(threads).build()?;
+    let print_file_name = printer.print_file_name;
+    let chunk_len = pool.current_num_threads() * FILES_PER_THREAD;
+    let mut paths = paths.into_iter();
+    let mut matched = false;
+    loop {
+        let chunk: Vec<_> = paths.by_ref().take(chunk_len).collect();
+        if chunk.is_empty() {
+            return Ok(matched);
+        }
+        let results: Vec<_> = pool.install(|| {
+            chunk.par_iter()
+                .map(|path| {
+                    let path = path.as_ref().ok()?;
+                    let mut lines: Vec<String> = Vec::new();
+                    let mut output = |line: &str| lines.push(line.to_string());
+                    let mut file_printer = Printer::new(&mut output, print_file_name, flags);
+                    let result = search_path(path, matcher, config, &mut file_printer);
+                    let printed_lines = file_printer.printed_any;
+                    Some((result, lines, printed_lines))
+                })
+                .collect()
+        });
+        for (path, searched) in chunk.into_iter().zip(results) {
+            let (path, (result, lines, printed_lines)) = match (path, searched) {
+                (Ok(path), Some(searched)) => (path, searched),
+                (Ok(_), None) => unreachable!("every readable path is searched"),
+                (Err(e), _) => {
+                    errors(&e.path, e.error);
+                    continue;
                 }
-            });
+            };
+            printer.append(&lines, printed_lines);
+            match result {
+                Ok(selected) => matched |= selected,
+                Err(e) => errors(&path, e.into()),
+            }
         }
     }
+}
+
+/// Searches a file, or standard input for `-`, returning whether any line was selected.
+fn search_path(path: &str, matcher: &Matcher, config: SearchConfig, printer: &mut Printer) -> io::Result<bool> {
+    let reader = open(path)?;
+    printer.print(Searcher::new(display_name(path), reader, matcher, config))
+}
+
+/// Opens a file, or standard input for `-`.
+fn open(path: &str) -> io::Result<Box<dyn BufRead>> {
+    if path == "-" {
+        Ok(Box::new(io::stdin().lock()))
+    } else {
+        Ok(Box::new(BufReader::new(File::open(path)?)))
+    }
+}
+
+fn display_name(path: &str) -> &str {
+    if path == "-" {
+        STDIN_NAME
+    } else {
+        path
+    }
+}
+
+/// Searches a single reader, reported as standard input, returning the lines to print.
+///
+/// Input is read line by line, so memory use does not depend on its size, and it does not need to
+/// be valid UTF-8: invalid sequences in printed lines are replaced with U+FFFD.
+pub fn grep_reader<P: Patterns + ?Sized, R: BufRead>(patterns: &P, flags: &Flags, reader: R) -> Result<Vec<String>, Error> {
+    let matcher = matcher(patterns, flags)?;
+    let mut res: Vec<String> = Vec::new();
+    let mut output = |line: &str| res.push(line.to_string());
+    let mut printer = Printer::new(&mut output, false, flags);
+    printer.print(Searcher::new(STDIN_NAME, reader, &matcher, SearchConfig::new(flags)))?;
     Ok(res)
 }
+
+/// A file that could not be searched.
+#[derive(Debug)]
+pub struct FileError {
+    pub path: String,
+    pub error: Error,
+}
+
+impl fmt::Display for FileError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}: {}", self.path, self.error)
+    }
+}
+
+impl error::Error for FileError {}
+
+/// Searches the same files as `grep`, yielding the selected lines and their context as `Match`es
+/// rather than formatted lines. `-c`, `-l`, `-L` and `-o` only change how matches are printed, so
+/// they are ignored here, but `-m` and the context flags are honoured.
+///
+/// Files that cannot be searched are yielded as errors and the search goes on with the next file.
+/// Fails at once if a pattern or a glob is invalid or a pattern file cannot be read.
+pub fn matches<P: Patterns + ?Sized>(patterns: &P, flags: &Flags, files: &[&str]) -> Result<Matches, Error> {
+    let matcher = matcher(patterns, flags)?;
+    let files = match files {
+        [] if flags.recursive => &["."],
+        [] => &["-"],
+        files => files,
+    };
+    let (paths, _) = walk::files(files, flags)?;
+    let config = Flags {
+        count: false,
+        print_file_names: false,
+        files_without_match: false,
+        only_matching: false,
+        ..flags.clone()
+    };
+    Ok(Matches {
+        matcher,
+        config: SearchConfig::new(&config),
+        paths: paths.into_iter(),
+        searcher: None,
+    })
+}
+
+/// The iterator returned by `matches`, searching one file at a time.
+pub struct Matches {
+    matcher: Matcher,
+    config: SearchConfig,
+    /// The files left to search, and the errors met walking to them, in path order.
+    paths: std::vec::IntoIter<Result<String, FileError>>,
+    searcher: Option<Searcher<Box<dyn BufRead>>>,
+}
+
+impl Iterator for Matches {
+    type Item = Result<Match, FileError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(searcher) = self.searcher.as_mut() {
+                match searcher.next() {
+                    Some(Ok(m)) => return Some(Ok(m)),
+                    Some(Err(e)) => {
+                        let path = searcher.path().to_string();
+                        self.searcher = None;
+                        return Some(Err(FileError { path, error: e.into() }));
+                    }
+                    None => self.searcher = None,
+                }
+            }
+            let path = match self.paths.next()? {
+                Ok(path) => path,
+                Err(e) => return Some(Err(e)),
+            };
+            match open(&path) {
+                Ok(reader) => {
+                    self.searcher = Some(Searcher::new(display_name(&path), reader, &self.matcher, self.config));
+                }
+                Err(e) => return Some(Err(FileError { path, error: e.into() })),
+            }
+        }
+    }
+}
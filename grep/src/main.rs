@@ -0,0 +1,55 @@
+use grep::{grep_each, Args};
+use std::env;
+use std::io::{self, BufWriter, IsTerminal, Write};
+use std::process;
+
+const USAGE: &str = "Usage: grep [OPTION]... PATTERNS [FILE]...";
+
+/// Exit statuses, as in GNU grep.
+const SELECTED: i32 = 0;
+const NOT_SELECTED: i32 = 1;
+const TROUBLE: i32 = 2;
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let mut args = match Args::parse(&args) {
+        Ok(args) => args,
+        Err(e) => {
+            eprintln!("grep: {}", e);
+            eprintln!("{}", USAGE);
+            process::exit(TROUBLE);
+        }
+    };
+    let dumb_terminal = env::var_os("TERM").is_some_and(|term| term == "dumb");
+    args.flags.resolve_color(io::stdout().is_terminal() && !dumb_terminal);
+    let files: Vec<&str> = args.files.iter().map(String::as_str).collect();
+
+    let stdout = io::stdout();
+    let mut stdout = BufWriter::new(stdout.lock());
+    let mut output = |line: &str| {
+        if writeln!(stdout, "{}", line).is_err() {
+            // Most likely a closed pipe, as with `grep ... | head`: nobody is reading any more.
+            process::exit(TROUBLE);
+        }
+    };
+    let mut failed = false;
+    let mut errors = |path: &str, e: failure::Error| {
+        eprintln!("grep: {}: {}", path, e);
+        failed = true;
+    };
+
+    let result = grep_each(&args.patterns, &args.flags, &files, &mut output, &mut errors);
+    let status = match result {
+        Ok(_) if failed => TROUBLE,
+        Ok(true) => SELECTED,
+        Ok(false) => NOT_SELECTED,
+        Err(e) => {
+            eprintln!("grep: {}", e);
+            TROUBLE
+        }
+    };
+    if stdout.flush().is_err() {
+        process::exit(TROUBLE);
+    }
+    process::exit(status);
+}
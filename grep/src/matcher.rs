@@ -0,0 +1,259 @@
+//! Testing lines against the patterns, with Unicode case folding for `-i`.
+
+use crate::Flags;
+use caseless::Caseless;
+use regex::bytes::{Regex, RegexBuilder};
+use std::iter;
+use std::ops::Range;
+
+/// One pattern or several, a line being selected when any of them matches: a `&str`, or a slice,
+/// array or `Vec` of them.
+pub trait Patterns {
+    fn patterns(&self) -> Vec<&str>;
+}
+
+impl Patterns for str {
+    fn patterns(&self) -> Vec<&str> {
+        vec![self]
+    }
+}
+
+impl Patterns for String {
+    fn patterns(&self) -> Vec<&str> {
+        vec![self]
+    }
+}
+
+impl<P: Patterns + ?Sized> Patterns for &P {
+    fn patterns(&self) -> Vec<&str> {
+        (**self).patterns()
+    }
+}
+
+impl<S: AsRef<str>> Patterns for [S] {
+    fn patterns(&self) -> Vec<&str> {
+        self.iter().map(AsRef::as_ref).collect()
+    }
+}
+
+impl<S: AsRef<str>, const N: usize> Patterns for [S; N] {
+    fn patterns(&self) -> Vec<&str> {
+        self[..].patterns()
+    }
+}
+
+impl<S: AsRef<str>> Patterns for Vec<S> {
+    fn patterns(&self) -> Vec<&str> {
+        self[..].patterns()
+    }
+}
+
+/// The compiled patterns a line is tested against.
+///
+/// All the patterns are compiled into a single regex, so that each line is searched once however
+/// many there are. For alternations of literals, as with `-F`, the regex engine looks for all of
+/// them at once with Aho-Corasick or SIMD literal search.
+///
+/// The regex engine only knows simple case folding, where each character folds to a single other
+/// one, so with `-i` it would not match `ß` with `ss` or `ﬁ` with `fi`. Instead, lines that are not
+/// plain ASCII are searched once fully case folded, and the characters of the pattern with a fold
+/// of several characters are replaced by it. Folding follows the Unicode defaults, without the
+/// Turkic mappings of dotted and dotless i.
+///
+/// Folding a line changes its characters, which would change what `.`, character classes, class
+/// escapes like `\w` and anchors inside the pattern match: `.` would no longer match the whole of
+/// `ß`, folded to `ss`. Patterns with any of those are left to the simple case folding of the
+/// engine, on the original lines.
+#[derive(Clone, Debug)]
+pub(crate) struct Matcher {
+    regex: Regex,
+    fold_case: bool,
+}
+
+impl Matcher {
+    /// Builds the regex from the patterns themselves, or their literal text with `-F`, folded with
+    /// `-i` and anchored to the whole line with `-x` or to word boundaries with `-w`. No pattern at
+    /// all matches no line.
+    pub(crate) fn new(patterns: &[String], flags: &Flags) -> Result<Self, regex::Error> {
+        let fold_case = flags.case_insensitive
+            && (flags.fixed_strings || patterns.iter().all(|pattern| matches_per_character(pattern)));
+        let mut patterns: Vec<String> = if flags.fixed_strings {
+            let mut literals: Vec<&str> = patterns.iter().map(String::as_str).collect();
+            // The regex engine prefers the first alternative matching at a position, where GNU grep
+            // prefers the longest, which matters to `-o` and `--color`.
+            literals.sort_by_key(|literal| std::cmp::Reverse(literal.len()));
+            literals.into_iter().map(regex::escape).collect()
+        } else {
+            patterns.to_vec()
+        };
+        if fold_case {
+            for pattern in patterns.iter_mut() {
+                *pattern = fold_pattern(pattern);
+            }
+        }
+        let pattern = if patterns.is_empty() {
+            // An empty class.
+            "[a&&b]".to_string()
+        } else {
+            let alternatives: Vec<_> = patterns.iter().map(|pattern| format!("(?:{})", pattern)).collect();
+            alternatives.join("|")
+        };
+        // As in GNU grep, a word match need not start or end with a word character itself, but it
+        // must not be preceded or followed by one.
+        let pattern = if flags.match_entire_lines {
+            format!("^(?:{})$", pattern)
+        } else if flags.match_words {
+            format!(r"\b{{start-half}}(?:{})\b{{end-half}}", pattern)
+        } else {
+            pattern
+        };
+        let regex = RegexBuilder::new(&pattern)
+            .case_insensitive(flags.case_insensitive)
+            .build()?;
+        Ok(Matcher {
+            regex,
+            fold_case,
+        })
+    }
+
+    pub(crate) fn is_match(&self, line: &[u8]) -> bool {
+        if self.needs_folding(line) {
+            self.regex.is_match(&Folded::new(line).bytes)
+        } else {
+            self.regex.is_match(line)
+        }
+    }
+
+    /// The non-empty ranges of `line` matched by the pattern.
+    pub(crate) fn find_ranges(&self, line: &[u8]) -> Vec<Range<usize>> {
+        if self.needs_folding(line) {
+            let folded = Folded::new(line);
+            let mut ranges: Vec<Range<usize>> = Vec::new();
+            let matches = self.regex.find_iter(&folded.bytes)
+                .map(|m| m.range())
+                .filter(|range| !range.is_empty())
+                .map(|range| folded.original(range));
+            for range in matches {
+                // Matches within the fold of a single character, like each `s` of `ß`, come from
+                // the same original text, reported once.
+                match ranges.last_mut() {
+                    Some(last) if range.start < last.end => last.end = last.end.max(range.end),
+                    _ => ranges.push(range),
+                }
+            }
+            ranges
+        } else {
+            self.regex.find_iter(line)
+                .map(|m| m.range())
+                .filter(|range| !range.is_empty())
+                .collect()
+        }
+    }
+
+    /// ASCII characters fold to a single character, which the regex engine handles by itself.
+    fn needs_folding(&self, line: &[u8]) -> bool {
+        self.fold_case && !line.is_ascii()
+    }
+}
+
+/// Whether a pattern matches the same text in a case-folded line as in the original one, having no
+/// `.`, character class, class escape or anchor, which match single characters or positions
+/// between them.
+fn matches_per_character(pattern: &str) -> bool {
+    let mut chars = pattern.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '.' | '[' | '^' | '$' => return false,
+            // Escaped punctuation is literal, where escaped letters are classes or anchors.
+            '\\' => match chars.next() {
+                Some(escaped) if escaped.is_alphanumeric() => return false,
+                _ => {}
+            },
+            _ => {}
+        }
+    }
+    true
+}
+
+/// Replaces the characters of a pattern that fold to several characters by their fold, except in
+/// escapes and character classes.
+fn fold_pattern(pattern: &str) -> String {
+    let mut folded = String::with_capacity(pattern.len());
+    let mut chars = pattern.chars().peekable();
+    let mut class_depth = 0;
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => {
+                folded.push(c);
+                folded.extend(chars.next());
+                continue;
+            }
+            '[' => {
+                class_depth += 1;
+                folded.push(c);
+                // A `]` first in a class, even a negated one, is literal.
+                if let Some(negation) = chars.next_if_eq(&'^') {
+                    folded.push(negation);
+                }
+                if let Some(bracket) = chars.next_if_eq(&']') {
+                    folded.push(bracket);
+                }
+                continue;
+            }
+            ']' if class_depth > 0 => class_depth -= 1,
+            _ if class_depth == 0 => {
+                let fold: String = iter::once(c).default_case_fold().collect();
+                if fold.chars().count() > 1 {
+                    folded.push_str("(?:");
+                    folded.push_str(&regex::escape(&fold));
+                    folded.push(')');
+                    continue;
+                }
+            }
+            _ => {}
+        }
+        folded.push(c);
+    }
+    folded
+}
+
+/// A line fully case folded. Invalid UTF-8 is kept as it is.
+struct Folded {
+    bytes: Vec<u8>,
+    // The offset in the original line of the character each folded byte comes from, followed by the
+    // length of the original line.
+    offsets: Vec<usize>,
+}
+
+impl Folded {
+    fn new(line: &[u8]) -> Self {
+        let mut bytes = Vec::with_capacity(line.len());
+        let mut offsets = Vec::with_capacity(line.len() + 1);
+        let mut start = 0;
+        for chunk in line.utf8_chunks() {
+            for (i, c) in chunk.valid().char_indices() {
+                for fold in iter::once(c).default_case_fold() {
+                    let mut buf = [0; 4];
+                    let encoded = fold.encode_utf8(&mut buf).as_bytes();
+                    bytes.extend_from_slice(encoded);
+                    offsets.extend(iter::repeat_n(start + i, encoded.len()));
+                }
+            }
+            start += chunk.valid().len();
+            for &b in chunk.invalid() {
+                bytes.push(b);
+                offsets.push(start);
+                start += 1;
+            }
+        }
+        offsets.push(line.len());
+        Folded { bytes, offsets }
+    }
+
+    /// The range of the original line covering the characters a non-empty folded range comes from.
+    fn original(&self, range: Range<usize>) -> Range<usize> {
+        let last = self.offsets[range.end - 1];
+        let end = self.offsets[range.end..].iter().copied().find(|&offset| offset > last);
+        self.offsets[range.start]..end.unwrap_or(last)
+    }
+}
@@ -0,0 +1,175 @@
+//! Formatting the lines found by a search like GNU grep, or as JSON lines.
+
+use crate::search::{LineKind, Match, Searcher};
+use crate::{Color, Flags};
+use serde_json::json;
+use std::io::{self, BufRead};
+use std::ops::Range;
+
+/// The SGR parameters GNU grep colors output with by default.
+const MATCH_COLOR: &str = "01;31";
+const FILE_NAME_COLOR: &str = "35";
+const LINE_NUMBER_COLOR: &str = "32";
+const SEPARATOR_COLOR: &str = "36";
+
+pub(crate) struct Printer<'a> {
+    output: &'a mut dyn FnMut(&str),
+    flags: &'a Flags,
+    path: String,
+    pub(crate) print_file_name: bool,
+    has_context: bool,
+    pub(crate) printed_any: bool,
+    last_printed: Option<usize>,
+}
+
+impl<'a> Printer<'a> {
+    pub(crate) fn new(output: &'a mut dyn FnMut(&str), print_file_name: bool, flags: &'a Flags) -> Self {
+        Printer {
+            output,
+            flags,
+            path: String::new(),
+            print_file_name,
+            has_context: (flags.after_context > 0 || flags.before_context > 0)
+                && !flags.only_matching
+                && !flags.json,
+            printed_any: false,
+            last_printed: None,
+        }
+    }
+
+    /// Prints everything found in one input: its lines, or the number of selected lines with `-c`,
+    /// or its name with `-l` or `-L`. Returns whether any line was selected.
+    pub(crate) fn print<R: BufRead>(&mut self, mut searcher: Searcher<R>) -> io::Result<bool> {
+        self.path = searcher.path().to_string();
+        self.last_printed = None;
+        let prints_lines = self.flags.prints_lines();
+        for m in &mut searcher {
+            let m = m?;
+            if prints_lines {
+                self.print_match(&m);
+            }
+        }
+
+        let count = searcher.selected();
+        if prints_lines {
+            return Ok(count > 0);
+        }
+        let mut formatted = String::new();
+        if self.flags.files_without_match || self.flags.print_file_names {
+            // `-L` takes precedence over `-l`.
+            let listed = if self.flags.files_without_match { count == 0 } else { count > 0 };
+            if !listed {
+                return Ok(count > 0);
+            }
+            self.paint(&mut formatted, FILE_NAME_COLOR, &self.path);
+        } else {
+            if self.print_file_name {
+                self.paint(&mut formatted, FILE_NAME_COLOR, &self.path);
+                self.paint(&mut formatted, SEPARATOR_COLOR, ":");
+            }
+            formatted.push_str(&count.to_string());
+        }
+        (self.output)(&formatted);
+        Ok(count > 0)
+    }
+
+    /// Prints the output of another printer, given whether it printed lines rather than only file
+    /// names or counts, separating it from the lines printed so far as a new group.
+    pub(crate) fn append(&mut self, output: &[String], printed_lines: bool) {
+        if self.has_context && self.printed_any && printed_lines {
+            self.group_separator();
+        }
+        self.printed_any |= printed_lines;
+        for line in output {
+            (self.output)(line);
+        }
+    }
+
+    /// Prints a line, or only its matched parts with `-o`, prefixed by the file name when
+    /// searching several files and by the line number with `-n`, each followed by `:` for selected
+    /// lines and `-` for context lines. With context, groups of lines that are not contiguous are
+    /// separated by `--`.
+    fn print_match(&mut self, m: &Match) {
+        if self.flags.json {
+            self.print_json(m);
+            return;
+        }
+        if self.has_context && self.printed_any && self.last_printed.map(|n| n + 1) != Some(m.line_number) {
+            self.group_separator();
+        }
+        self.printed_any = true;
+        self.last_printed = Some(m.line_number);
+
+        if self.flags.only_matching {
+            for range in m.submatch_ranges.iter() {
+                let whole = 0..range.len();
+                self.print_line(m, &m.line[range.clone()], &[whole]);
+            }
+        } else {
+            self.print_line(m, &m.line, &m.submatch_ranges);
+        }
+    }
+
+    /// Prints `text`, from the line of `m`, with its `highlights` colored as matches.
+    fn print_line(&mut self, m: &Match, text: &[u8], highlights: &[Range<usize>]) {
+        let separator = match m.kind {
+            LineKind::Selected => ":",
+            LineKind::Context => "-",
+        };
+        let mut formatted = String::new();
+        if self.print_file_name {
+            self.paint(&mut formatted, FILE_NAME_COLOR, &m.path);
+            self.paint(&mut formatted, SEPARATOR_COLOR, separator);
+        }
+        if self.flags.print_line_numbers {
+            self.paint(&mut formatted, LINE_NUMBER_COLOR, &m.line_number.to_string());
+            self.paint(&mut formatted, SEPARATOR_COLOR, separator);
+        }
+        let mut end = 0;
+        for range in highlights {
+            formatted.push_str(&String::from_utf8_lossy(&text[end..range.start]));
+            self.paint(&mut formatted, MATCH_COLOR, &String::from_utf8_lossy(&text[range.clone()]));
+            end = range.end;
+        }
+        formatted.push_str(&String::from_utf8_lossy(&text[end..]));
+        (self.output)(&formatted);
+    }
+
+    fn group_separator(&mut self) {
+        let mut formatted = String::new();
+        self.paint(&mut formatted, SEPARATOR_COLOR, "--");
+        (self.output)(&formatted);
+    }
+
+    /// Appends `text` to `formatted`, wrapped in escape sequences setting the given SGR parameters
+    /// and resetting them afterwards when coloring. Like GNU grep, each sequence is followed by an
+    /// erase to the end of the line, so that background colors do not spill over.
+    fn paint(&self, formatted: &mut String, color: &str, text: &str) {
+        if self.flags.color == Color::Always {
+            formatted.push_str(&format!("\x1b[{}m\x1b[K{}\x1b[m\x1b[K", color, text));
+        } else {
+            formatted.push_str(text);
+        }
+    }
+
+    /// Prints a line as a JSON object on a line of its own, with invalid UTF-8 replaced by U+FFFD.
+    fn print_json(&mut self, m: &Match) {
+        let kind = match m.kind {
+            LineKind::Selected => "match",
+            LineKind::Context => "context",
+        };
+        let submatches: Vec<_> = m.submatch_ranges.iter()
+            .map(|range| json!({ "start": range.start, "end": range.end }))
+            .collect();
+        let value = json!({
+            "type": kind,
+            "path": m.path,
+            "line_number": m.line_number,
+            "byte_offset": m.byte_offset,
+            "line": String::from_utf8_lossy(&m.line),
+            "submatches": submatches,
+        });
+        self.printed_any = true;
+        (self.output)(&value.to_string());
+    }
+}
@@ -0,0 +1,196 @@
+//! Searching a single input line by line, yielding the lines it finds as `Match`es.
+
+use crate::matcher::Matcher;
+use crate::Flags;
+use std::collections::VecDeque;
+use std::io::{self, BufRead};
+use std::ops::Range;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum LineKind {
+    /// A line selected by the pattern, or not matching it with `-v`.
+    Selected,
+    /// A line printed around a selected one with `-A`, `-B` or `-C`.
+    Context,
+}
+
+/// A line found by a search.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Match {
+    /// The file the line is in, or `(standard input)`.
+    pub path: String,
+    pub kind: LineKind,
+    /// The number of the line in its file, starting at 1.
+    pub line_number: usize,
+    /// The offset of the start of the line from the start of its file.
+    pub byte_offset: u64,
+    /// The line, without its line ending.
+    pub line: Vec<u8>,
+    /// The non-empty ranges of `line` matched by the pattern, empty for context lines and for lines
+    /// selected with `-v`.
+    pub submatch_ranges: Vec<Range<usize>>,
+}
+
+/// What to look for in each input, as decided by the flags.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct SearchConfig {
+    invert: bool,
+    before_context: usize,
+    after_context: usize,
+    max_count: Option<usize>,
+}
+
+impl SearchConfig {
+    /// Context is only looked for when lines are printed whole, and listing files with `-l` or `-L`
+    /// only needs to know whether there is a selected line at all.
+    pub(crate) fn new(flags: &Flags) -> Self {
+        let (before_context, after_context) = if flags.prints_lines() && !flags.only_matching {
+            (flags.before_context, flags.after_context)
+        } else {
+            (0, 0)
+        };
+        let max_count = if flags.prints_lines() || flags.count {
+            flags.max_count
+        } else {
+            Some(flags.max_count.map_or(1, |n| n.min(1)))
+        };
+        SearchConfig {
+            invert: flags.invert,
+            before_context,
+            after_context,
+            max_count,
+        }
+    }
+}
+
+/// Reads an input line by line, so that memory use does not depend on its size, and yields its
+/// selected lines and their context. Reading stops after `-m` selected lines and their
+/// after-context.
+pub(crate) struct Searcher<R> {
+    reader: R,
+    path: String,
+    matcher: Matcher,
+    config: SearchConfig,
+    line_number: usize,
+    byte_offset: u64,
+    count: usize,
+    // The last lines not yielded yet, kept as before-context for the next selected line.
+    before: VecDeque<(usize, u64, Vec<u8>)>,
+    after_remaining: usize,
+    pending: VecDeque<Match>,
+    buf: Vec<u8>,
+    done: bool,
+}
+
+impl<R: BufRead> Searcher<R> {
+    pub(crate) fn new(path: &str, reader: R, matcher: &Matcher, config: SearchConfig) -> Self {
+        Searcher {
+            reader,
+            path: path.to_string(),
+            matcher: matcher.clone(),
+            config,
+            line_number: 0,
+            byte_offset: 0,
+            count: 0,
+            before: VecDeque::with_capacity(config.before_context),
+            after_remaining: 0,
+            pending: VecDeque::new(),
+            buf: Vec::new(),
+            done: false,
+        }
+    }
+
+    pub(crate) fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// The number of lines selected so far.
+    pub(crate) fn selected(&self) -> usize {
+        self.count
+    }
+
+    fn next_match(&mut self) -> io::Result<Option<Match>> {
+        loop {
+            if let Some(m) = self.pending.pop_front() {
+                return Ok(Some(m));
+            }
+            let max_reached = self.config.max_count == Some(self.count);
+            if max_reached && self.after_remaining == 0 {
+                return Ok(None);
+            }
+            self.buf.clear();
+            let len = self.reader.read_until(b'\n', &mut self.buf)?;
+            if len == 0 {
+                return Ok(None);
+            }
+            self.line_number += 1;
+            let (line_number, byte_offset) = (self.line_number, self.byte_offset);
+            self.byte_offset += len as u64;
+            let line = trim_line_ending(&self.buf);
+
+            if !max_reached && self.matcher.is_match(line) != self.config.invert {
+                self.count += 1;
+                let submatch_ranges = if self.config.invert {
+                    Vec::new()
+                } else {
+                    self.matcher.find_ranges(line)
+                };
+                let selected = Match {
+                    path: self.path.clone(),
+                    kind: LineKind::Selected,
+                    line_number,
+                    byte_offset,
+                    line: line.to_vec(),
+                    submatch_ranges,
+                };
+                let before: Vec<_> = self.before.drain(..).collect();
+                for (line_number, byte_offset, line) in before {
+                    let context = self.context(line_number, byte_offset, line);
+                    self.pending.push_back(context);
+                }
+                self.pending.push_back(selected);
+                self.after_remaining = self.config.after_context;
+            } else if self.after_remaining > 0 {
+                self.after_remaining -= 1;
+                let line = line.to_vec();
+                return Ok(Some(self.context(line_number, byte_offset, line)));
+            } else if self.config.before_context > 0 {
+                if self.before.len() == self.config.before_context {
+                    self.before.pop_front();
+                }
+                self.before.push_back((line_number, byte_offset, line.to_vec()));
+            }
+        }
+    }
+
+    fn context(&self, line_number: usize, byte_offset: u64, line: Vec<u8>) -> Match {
+        Match {
+            path: self.path.clone(),
+            kind: LineKind::Context,
+            line_number,
+            byte_offset,
+            line,
+            submatch_ranges: Vec::new(),
+        }
+    }
+}
+
+impl<R: BufRead> Iterator for Searcher<R> {
+    type Item = io::Result<Match>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let next = self.next_match().transpose();
+        if !matches!(next, Some(Ok(_))) {
+            self.done = true;
+        }
+        next
+    }
+}
+
+fn trim_line_ending(line: &[u8]) -> &[u8] {
+    let line = line.strip_suffix(b"\n").unwrap_or(line);
+    line.strip_suffix(b"\r").unwrap_or(line)
+}
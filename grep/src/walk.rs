@@ -0,0 +1,92 @@
+//! Expansion of the paths given to `grep` into the files to search.
+
+use crate::{FileError, Flags};
+use failure::Error;
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use ignore::WalkBuilder;
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+
+/// How many leading bytes are inspected to decide whether a file is binary.
+const BINARY_DETECTION_LEN: u64 = 8192;
+
+/// Returns the files to search, along with the files and directories that cannot be read in their
+/// place, and whether file names should be printed alongside matches.
+///
+/// With `-r`, directories are walked in file name order, honouring the `.gitignore` files found
+/// within them but not those of their parents, which may belong to unrelated trees, skipping
+/// `.git` and `--exclude-dir` directories and skipping binary files. `--include` and `--exclude`
+/// are matched against file base names, for walked files and explicitly given ones alike.
+/// Symbolic links met while walking are skipped, unless `-R` asks to follow them.
+pub(crate) fn files(paths: &[&str], flags: &Flags) -> Result<(Vec<Result<String, FileError>>, bool), Error> {
+    let include = glob_set(&flags.include)?;
+    let exclude = glob_set(&flags.exclude)?;
+    let exclude_dir = glob_set(&flags.exclude_dir)?;
+    let selected = |path: &Path| {
+        let name = path.file_name().map(Path::new).unwrap_or(path);
+        (flags.include.is_empty() || include.is_match(name)) && !exclude.is_match(name)
+    };
+
+    let mut res = Vec::new();
+    let mut print_file_names = paths.len() > 1;
+    for &path in paths {
+        if !flags.recursive || !Path::new(path).is_dir() {
+            if selected(Path::new(path)) {
+                res.push(Ok(path.to_string()));
+            }
+            continue;
+        }
+
+        print_file_names = true;
+        let exclude_dir = exclude_dir.clone();
+        let walker = WalkBuilder::new(path)
+            .standard_filters(false)
+            .git_ignore(true)
+            .parents(false)
+            .follow_links(flags.dereference)
+            .require_git(false)
+            .sort_by_file_name(|a, b| a.cmp(b))
+            .filter_entry(move |entry| {
+                let is_dir = entry.file_type().is_some_and(|t| t.is_dir());
+                !(is_dir && entry.depth() > 0
+                    && (entry.file_name() == ".git" || exclude_dir.is_match(entry.file_name())))
+            })
+            .build();
+        for entry in walker {
+            let entry = match entry {
+                Ok(entry) => entry,
+                Err(e) => {
+                    res.push(Err(FileError { path: path.to_string(), error: e.into() }));
+                    continue;
+                }
+            };
+            let is_file = entry.file_type().is_some_and(|t| t.is_file());
+            if !is_file || !selected(entry.path()) {
+                continue;
+            }
+            let file = entry.path().to_string_lossy().into_owned();
+            match is_binary(entry.path()) {
+                Ok(false) => res.push(Ok(file)),
+                Ok(true) => {}
+                Err(error) => res.push(Err(FileError { path: file, error })),
+            }
+        }
+    }
+    Ok((res, print_file_names))
+}
+
+fn glob_set(globs: &[String]) -> Result<GlobSet, globset::Error> {
+    let mut builder = GlobSetBuilder::new();
+    for glob in globs {
+        builder.add(Glob::new(glob)?);
+    }
+    builder.build()
+}
+
+/// A file is considered binary, like GNU grep does, if it has a NUL byte near its start.
+fn is_binary(path: &Path) -> Result<bool, Error> {
+    let mut head = Vec::new();
+    File::open(path)?.take(BINARY_DETECTION_LEN).read_to_end(&mut head)?;
+    Ok(head.contains(&0))
+}
@@ -1,6 +1,13 @@
-use grep::{grep, Flags};
+// The fixtures keep their original, explicitly static types.
+#![allow(clippy::redundant_static_lifetimes)]
+
+use grep::{grep, grep_reader, matches, Args, ArgsError, Flags, LineKind, Match};
 
 use std::fs;
+use std::env;
+use std::io::{self, Read};
+use std::process::Command;
+use std::path::PathBuf;
 
 static ILIAD_CONTENT: &'static str = "Achilles sing, O Goddess! Peleus' son;
 His wrath pernicious, who ten thousand woes
@@ -499,3 +506,1012 @@ set_up_test_case!(
         ]
     )
 );
+
+// Test regular expression patterns
+
+set_up_test_case!(#[test]
+test_one_file_regex_pattern(
+    pattern = r"^Of \w+,",
+    flags = ["-n"],
+    files = ["paradise_lost.txt"],
+    expected = ["7:Of Oreb, or of Sinai, didst inspire"]
+));
+
+set_up_test_case!(#[test]
+test_one_file_regex_pattern_caseinsensitive_and_match_entire_lines_flags(
+    pattern = r"of atreus.*men\.|to dogs.*prey,",
+    flags = ["-i", "-x"],
+    files = ["iliad.txt"],
+    expected = [
+        "To dogs and to all ravening fowls a prey,",
+        "Of Atreus, Agamemnon, King of men."
+    ]
+));
+
+set_up_test_case!(#[test]
+test_one_file_regex_pattern_inverted_flag(
+    pattern = r"[aeiou]s\.$",
+    flags = ["-v"],
+    files = ["midsummer_night.txt"],
+    expected = [
+        "I do entreat your grace to pardon me.",
+        "I know not by what power I am made bold,",
+        "Nor how it may concern my modesty,",
+        "In such a presence here to plead my thoughts;",
+        "But I beseech your grace that I may know",
+        "The worst that may befall me in this case,"
+    ]
+));
+
+set_up_test_case!(#[test]
+test_one_file_fixed_strings_flag(
+    pattern = "(so stood the will of Jove)",
+    flags = ["-F"],
+    files = ["iliad.txt"],
+    expected = ["And Heroes gave (so stood the will of Jove)"]
+));
+
+set_up_test_case!(#[test]
+test_one_file_fixed_strings_flag_does_not_interpret_metacharacters(
+    pattern = "Of.*men",
+    flags = ["-F"],
+    files = ["iliad.txt"],
+    expected = []
+));
+
+#[test]
+fn test_invalid_regex_returns_error() {
+    let pattern = "(Agamemnon";
+
+    let flags = Flags::new(&[]);
+
+    let files = vec!["test_invalid_regex_returns_error_iliad.txt"];
+
+    let test_fixture = Fixture::new(&files);
+
+    test_fixture.set_up();
+
+    assert!(grep(pattern, &flags, &files).is_err());
+}
+
+// Test case folding and matching words
+
+#[test]
+fn test_case_insensitive_flag_folds_case_fully() {
+    let input = "Die Straße\nDIE STRASSE\ndie strasse\nDer ﬁsch\n";
+
+    let flags = Flags::new(&["-i", "-n"]);
+
+    for pattern in &["straße", "STRASSE", "STRAẞE"] {
+        assert_eq!(
+            grep_reader(pattern, &flags, input.as_bytes()).unwrap(),
+            vec!["1:Die Straße", "2:DIE STRASSE", "3:die strasse"],
+            "pattern {:?}",
+            pattern
+        );
+    }
+    assert_eq!(grep_reader("FISCH", &flags, input.as_bytes()).unwrap(), vec!["4:Der ﬁsch"]);
+    assert_eq!(grep_reader("[ß]", &Flags::new(&[]), input.as_bytes()).unwrap(), vec!["Die Straße"]);
+}
+
+#[test]
+fn test_case_insensitive_flag_reports_original_text() {
+    let flags = Flags::new(&["-i", "-o"]);
+
+    assert_eq!(
+        grep_reader("strasse|fisch", &flags, "Die Straße, der ﬁsch\n".as_bytes()).unwrap(),
+        vec!["Straße", "ﬁsch"]
+    );
+}
+
+#[test]
+fn test_case_insensitive_flag_keeps_dots_and_classes_to_whole_characters() {
+    let input = "ß\n";
+
+    assert_eq!(grep_reader(".", &Flags::new(&["-x"]), input.as_bytes()).unwrap(), vec!["ß"]);
+    assert_eq!(grep_reader(".", &Flags::new(&["-i", "-x"]), input.as_bytes()).unwrap(), vec!["ß"]);
+    assert_eq!(grep_reader(".", &Flags::new(&["-i", "-o"]), input.as_bytes()).unwrap(), vec!["ß"]);
+    assert_eq!(grep_reader("[ß]", &Flags::new(&["-i"]), input.as_bytes()).unwrap(), vec!["ß"]);
+    assert_eq!(grep_reader(r"^\w$", &Flags::new(&["-i"]), input.as_bytes()).unwrap(), vec!["ß"]);
+}
+
+#[test]
+fn test_case_insensitive_flag_reports_each_folded_character_once() {
+    let flags = Flags::new(&["-i", "-o"]);
+
+    assert_eq!(grep_reader("s", &flags, "ß\n".as_bytes()).unwrap(), vec!["ß"]);
+    assert_eq!(grep_reader("s", &flags, "ßs ß\n".as_bytes()).unwrap(), vec!["ß", "s", "ß"]);
+}
+
+#[test]
+fn test_case_insensitive_flag_with_fixed_strings_and_greek() {
+    let flags = Flags::new(&["-i", "-F"]);
+
+    assert_eq!(
+        grep_reader("ΣΊΣΥΦΟΣ.", &flags, "ο σίσυφος.\nο σίσυφος\n".as_bytes()).unwrap(),
+        vec!["ο σίσυφος."]
+    );
+}
+
+#[test]
+fn test_word_regexp_flag() {
+    let files = ["test_word_regexp_flag_iliad.txt", "test_word_regexp_flag_in_the_white_night.txt"];
+
+    let test_fixture = Fixture::new(&files);
+
+    test_fixture.set_up();
+
+    let flags = Flags::new(&["-w", "-n"]);
+
+    assert_eq!(
+        grep("son", &flags, &files).unwrap(),
+        vec![
+            "test_word_regexp_flag_iliad.txt:1:Achilles sing, O Goddess! Peleus' son;",
+            "test_word_regexp_flag_iliad.txt:8:The noble Chief Achilles from the son",
+        ]
+    );
+    assert!(grep("Achill", &flags, &files).unwrap().is_empty());
+    assert!(grep("меся", &flags, &files).unwrap().is_empty());
+    assert_eq!(
+        grep("месяц", &flags, &files).unwrap(),
+        vec![
+            "test_word_regexp_flag_in_the_white_night.txt:1:Белой ночью месяц красный",
+            "test_word_regexp_flag_in_the_white_night.txt:8:Красный месяц, тихий шум?..",
+        ]
+    );
+}
+
+#[test]
+fn test_word_regexp_flag_with_non_word_characters_and_later_matches() {
+    let input = "x-foo\nx -foo\nfoobar foo\n";
+
+    let flags = Flags::new(&["-w", "-o", "-n"]);
+
+    assert_eq!(grep_reader("-foo", &flags, input.as_bytes()).unwrap(), vec!["2:-foo"]);
+    assert_eq!(grep_reader("foo", &flags, input.as_bytes()).unwrap(), vec!["1:foo", "2:foo", "3:foo"]);
+}
+
+#[test]
+fn test_line_regexp_flag_takes_precedence_over_word_regexp_flag() {
+    let flags = Flags::new(&["-x", "-w"]);
+
+    assert!(grep_reader("son", &flags, ILIAD_CONTENT.as_bytes()).unwrap().is_empty());
+}
+
+// Test multiple patterns
+
+#[test]
+fn test_multiple_patterns() {
+    let flags = Flags::new(&["-n"]);
+
+    assert_eq!(
+        grep_reader(&["Agamemnon", "Achilles"], &flags, ILIAD_CONTENT.as_bytes()).unwrap(),
+        vec![
+            "1:Achilles sing, O Goddess! Peleus' son;",
+            "8:The noble Chief Achilles from the son",
+            "9:Of Atreus, Agamemnon, King of men.",
+        ]
+    );
+}
+
+#[test]
+fn test_regexp_flag_adds_patterns() {
+    let flags = Flags::new(&["-e", "Agamemnon", "--regexp=Achilles", "-c"]);
+
+    assert_eq!(grep_reader("Ades", &flags, ILIAD_CONTENT.as_bytes()).unwrap(), vec!["4"]);
+    assert_eq!(grep_reader(&Vec::<String>::new(), &flags, ILIAD_CONTENT.as_bytes()).unwrap(), vec!["3"]);
+}
+
+#[test]
+fn test_multiple_fixed_strings_prefer_longest_match() {
+    let flags = Flags::new(&["-F", "-o"]);
+
+    assert_eq!(
+        grep_reader(&["Aga", "Agamemnon", "King"], &flags, ILIAD_CONTENT.as_bytes()).unwrap(),
+        vec!["Agamemnon", "King"]
+    );
+}
+
+#[test]
+fn test_file_flag_reads_patterns() {
+    let fixture = DirFixture::new(
+        "test_file_flag_reads_patterns",
+        &[("patterns", b"Agamemnon\nson\n"), ("empty", b"")],
+    );
+
+    let flags = Flags::new(&["-f", &fixture.path("patterns"), "-e", "Ades", "-n"]);
+
+    assert_eq!(
+        grep_reader(&Vec::<String>::new(), &flags, ILIAD_CONTENT.as_bytes()).unwrap(),
+        vec![
+            "1:Achilles sing, O Goddess! Peleus' son;",
+            "4:Illustrious into Ades premature,",
+            "8:The noble Chief Achilles from the son",
+            "9:Of Atreus, Agamemnon, King of men.",
+        ]
+    );
+
+    let flags = Flags::new(&["-f", &fixture.path("empty"), "-c"]);
+
+    assert_eq!(grep_reader(&Vec::<String>::new(), &flags, ILIAD_CONTENT.as_bytes()).unwrap(), vec!["0"]);
+
+    let flags = Flags::new(&["-f", &fixture.path("empty"), "-c", "-v"]);
+
+    assert_eq!(grep_reader(&Vec::<String>::new(), &flags, ILIAD_CONTENT.as_bytes()).unwrap(), vec!["9"]);
+}
+
+#[test]
+fn test_file_flag_with_nonexistent_file_returns_error() {
+    let flags = Flags::new(&["-f", "test_file_flag_nonexistent_patterns"]);
+
+    let error = grep_reader(&Vec::<String>::new(), &flags, ILIAD_CONTENT.as_bytes()).unwrap_err();
+
+    assert!(error.to_string().starts_with("test_file_flag_nonexistent_patterns: "));
+}
+
+// Test printing context lines
+
+set_up_test_case!(#[test]
+test_one_file_after_context_flag(
+    pattern = "Agamemnon|Illustrious",
+    flags = ["-A", "1"],
+    files = ["iliad.txt"],
+    expected = [
+        "Illustrious into Ades premature,",
+        "And Heroes gave (so stood the will of Jove)",
+        "--",
+        "Of Atreus, Agamemnon, King of men."
+    ]
+));
+
+set_up_test_case!(#[test]
+test_one_file_before_context_flag_with_line_numbers(
+    pattern = "Forbidden",
+    flags = ["-n", "-B2"],
+    files = ["paradise_lost.txt"],
+    expected = [
+        "1-Of Mans First Disobedience, and the Fruit",
+        "2:Of that Forbidden Tree, whose mortal tast"
+    ]
+));
+
+set_up_test_case!(#[test]
+test_one_file_overlapping_context_is_merged(
+    pattern = "may",
+    flags = ["-n", "-C", "1"],
+    files = ["midsummer_night.txt"],
+    expected = [
+        "2-I know not by what power I am made bold,",
+        "3:Nor how it may concern my modesty,",
+        "4-In such a presence here to plead my thoughts;",
+        "5:But I beseech your grace that I may know",
+        "6:The worst that may befall me in this case,",
+        "7-If I refuse to wed Demetrius."
+    ]
+));
+
+set_up_test_case!(#[test]
+test_one_file_explicit_context_flag_takes_precedence_over_combined(
+    pattern = "Seat",
+    flags = ["-A0", "-C1"],
+    files = ["paradise_lost.txt"],
+    expected = [
+        "With loss of Eden, till one greater Man",
+        "Restore us, and regain the blissful Seat,"
+    ]
+));
+
+#[test]
+fn test_multiple_files_context_flag() {
+    let files = [
+        "test_multiple_files_context_flag_iliad.txt",
+        "test_multiple_files_context_flag_paradise_lost.txt",
+    ];
+
+    let expected = [
+        "test_multiple_files_context_flag_iliad.txt-8-The noble Chief Achilles from the son",
+        "test_multiple_files_context_flag_iliad.txt:9:Of Atreus, Agamemnon, King of men.",
+        "--",
+        "test_multiple_files_context_flag_paradise_lost.txt:1:Of Mans First Disobedience, and the Fruit",
+        "test_multiple_files_context_flag_paradise_lost.txt-2-Of that Forbidden Tree, whose mortal tast",
+    ];
+
+    process_grep_case("Agamemnon|Disobedience", &["-n", "-C1"], &files, &expected);
+}
+
+set_up_test_case!(#[test]
+test_one_file_context_flag_with_print_file_names_flag(
+    pattern = "Agamemnon",
+    flags = ["-l", "-C", "3"],
+    files = ["iliad.txt"],
+    prefix_expected = ["iliad.txt"]
+));
+
+// Test counting, extracting matches and limiting their number
+
+set_up_test_case!(#[test]
+test_one_file_count_flag(
+    pattern = "may",
+    flags = ["-c"],
+    files = ["midsummer_night.txt"],
+    expected = ["3"]
+));
+
+set_up_test_case!(#[test]
+test_one_file_count_and_inverted_flags(
+    pattern = "Of",
+    flags = ["-c", "-v"],
+    files = ["paradise_lost.txt"],
+    expected = ["5"]
+));
+
+set_up_test_case!(#[test]
+test_multiple_files_count_flag(
+    pattern = "who",
+    flags = ["-c"],
+    files = ["iliad.txt", "midsummer_night.txt", "paradise_lost.txt"],
+    prefix_expected = ["iliad.txt:1", "midsummer_night.txt:0", "paradise_lost.txt:2"]
+));
+
+set_up_test_case!(#[test]
+test_one_file_only_matching_flag(
+    pattern = r"A\w+",
+    flags = ["-o", "-n"],
+    files = ["iliad.txt"],
+    expected = [
+        "1:Achilles",
+        "3:Achaia",
+        "4:Ades",
+        "5:And",
+        "8:Achilles",
+        "9:Atreus",
+        "9:Agamemnon"
+    ]
+));
+
+set_up_test_case!(#[test]
+test_one_file_only_matching_and_inverted_flags(
+    pattern = "Agamemnon",
+    flags = ["-o", "-v"],
+    files = ["iliad.txt"],
+    expected = []
+));
+
+set_up_test_case!(#[test]
+test_one_file_max_count_flag(
+    pattern = "may",
+    flags = ["-n", "-m", "2"],
+    files = ["midsummer_night.txt"],
+    expected = [
+        "3:Nor how it may concern my modesty,",
+        "5:But I beseech your grace that I may know"
+    ]
+));
+
+set_up_test_case!(#[test]
+test_one_file_max_count_flag_prints_trailing_context(
+    pattern = "may",
+    flags = ["-n", "-m1", "-A2"],
+    files = ["midsummer_night.txt"],
+    expected = [
+        "3:Nor how it may concern my modesty,",
+        "4-In such a presence here to plead my thoughts;",
+        "5-But I beseech your grace that I may know"
+    ]
+));
+
+set_up_test_case!(#[test]
+test_multiple_files_max_count_and_count_flags(
+    pattern = "a",
+    flags = ["-c", "-m", "3"],
+    files = ["iliad.txt", "midsummer_night.txt"],
+    prefix_expected = ["iliad.txt:3", "midsummer_night.txt:3"]
+));
+
+set_up_test_case!(#[test]
+test_multiple_files_files_without_match_flag(
+    pattern = "who",
+    flags = ["-L"],
+    files = ["iliad.txt", "midsummer_night.txt", "paradise_lost.txt"],
+    prefix_expected = ["midsummer_night.txt"]
+));
+
+// Test searching files in parallel
+
+#[test]
+fn test_parallel_search_matches_serial_search() {
+    let files = [
+        "test_parallel_search_iliad.txt",
+        "test_parallel_search_midsummer_night.txt",
+        "test_parallel_search_paradise_lost.txt",
+        "test_parallel_search_in_the_white_night.txt",
+    ];
+
+    let test_fixture = Fixture::new(&files);
+
+    test_fixture.set_up();
+
+    let cases: &[(&str, &[&str])] = &[
+        ("a", &["-n"]),
+        ("the", &["-i", "-C1"]),
+        ("who", &["-l"]),
+        ("who", &["-c"]),
+        ("Of", &["-v", "-m2", "-A1"]),
+        (r"\w+ly", &["-o"]),
+    ];
+    for &(pattern, flags) in cases {
+        let serial = grep(pattern, &Flags::new(flags), &files).unwrap();
+        for threads in &["1", "2", "0"] {
+            let parallel_flags: Vec<&str> = flags.iter().copied().chain(vec!["-j", threads]).collect();
+            let parallel = grep(pattern, &Flags::new(&parallel_flags), &files).unwrap();
+            assert_eq!(parallel, serial, "pattern {:?} with flags {:?}", pattern, parallel_flags);
+        }
+    }
+}
+
+#[test]
+fn test_parallel_search_with_nonexistent_file_returns_error() {
+    let files = ["test_parallel_search_with_nonexistent_file_iliad.txt"];
+
+    let test_fixture = Fixture::new(&files);
+
+    test_fixture.set_up();
+
+    let flags = Flags::new(&["-j", "2"]);
+
+    assert!(grep("Agamemnon", &flags, &[files[0], "test_parallel_search_nonexistent.txt"]).is_err());
+}
+
+// Test streaming input
+
+#[test]
+fn test_reader_with_invalid_utf8_and_crlf_line_endings() {
+    let input: &[u8] = b"Achilles sing\r\nof \xffAgamemnon\xfe\r\nKing of men\r\n";
+
+    let flags = Flags::new(&["-n"]);
+
+    assert_eq!(
+        grep_reader("Agamemnon", &flags, input).unwrap(),
+        vec!["2:of \u{fffd}Agamemnon\u{fffd}"]
+    );
+}
+
+#[test]
+fn test_reader_without_trailing_newline() {
+    let input: &[u8] = b"Of Atreus,\nAgamemnon";
+
+    let flags = Flags::new(&[]);
+
+    assert_eq!(grep_reader("Agamemnon", &flags, input).unwrap(), vec!["Agamemnon"]);
+}
+
+#[test]
+fn test_reader_print_file_names_flag_reports_standard_input() {
+    let flags = Flags::new(&["-l"]);
+
+    assert_eq!(
+        grep_reader("Agamemnon", &flags, ILIAD_CONTENT.as_bytes()).unwrap(),
+        vec!["(standard input)"]
+    );
+}
+
+/// Endless lines of filler, with a marker line every `period` lines.
+struct Filler {
+    line: usize,
+    period: usize,
+}
+
+impl Read for Filler {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let line = if self.line.is_multiple_of(self.period) {
+            format!("marker {}\n", self.line)
+        } else {
+            "the quick brown fox jumps over the lazy dog\n".to_string()
+        };
+        self.line += 1;
+        let len = line.len().min(buf.len());
+        buf[..len].copy_from_slice(&line.as_bytes()[..len]);
+        Ok(len)
+    }
+}
+
+#[test]
+fn test_reader_streams_large_input() {
+    let input = io::BufReader::new(Filler { line: 1, period: 100_000 }.take(1 << 24));
+
+    let flags = Flags::new(&["-n", "-B1"]);
+
+    let mut expected = Vec::new();
+    for line in (100_000..=300_000).step_by(100_000) {
+        if line > 100_000 {
+            expected.push("--".to_string());
+        }
+        expected.push(format!("{}-the quick brown fox jumps over the lazy dog", line - 1));
+        expected.push(format!("{}:marker {}", line, line));
+    }
+
+    assert_eq!(grep_reader("marker", &flags, input).unwrap(), expected);
+}
+
+// Test structured match results
+
+#[test]
+fn test_matches_report_positions_and_submatches() {
+    let files = ["test_matches_report_positions_and_submatches_iliad.txt"];
+
+    let test_fixture = Fixture::new(&files);
+
+    test_fixture.set_up();
+
+    let flags = Flags::new(&["-B1"]);
+
+    let found: Vec<Match> = matches("son|Achi", &flags, &files).unwrap().map(Result::unwrap).collect();
+
+    let line = |n: usize| ILIAD_CONTENT.lines().nth(n - 1).unwrap();
+    let offset = |n: usize| ILIAD_CONTENT.lines().take(n - 1).map(|l| l.len() as u64 + 1).sum::<u64>();
+    let expected = vec![
+        Match {
+            path: files[0].to_string(),
+            kind: LineKind::Selected,
+            line_number: 1,
+            byte_offset: 0,
+            line: line(1).as_bytes().to_vec(),
+            submatch_ranges: vec![0..4, 34..37],
+        },
+        Match {
+            path: files[0].to_string(),
+            kind: LineKind::Context,
+            line_number: 7,
+            byte_offset: offset(7),
+            line: line(7).as_bytes().to_vec(),
+            submatch_ranges: vec![],
+        },
+        Match {
+            path: files[0].to_string(),
+            kind: LineKind::Selected,
+            line_number: 8,
+            byte_offset: offset(8),
+            line: line(8).as_bytes().to_vec(),
+            submatch_ranges: vec![16..20, 34..37],
+        },
+    ];
+
+    assert_eq!(found, expected);
+}
+
+#[test]
+fn test_matches_yield_errors_and_go_on() {
+    let files = ["test_matches_yield_errors_and_go_on_iliad.txt"];
+
+    let test_fixture = Fixture::new(&files);
+
+    test_fixture.set_up();
+
+    let flags = Flags::new(&[]);
+
+    let found: Vec<_> = matches("Agamemnon", &flags, &["test_matches_nonexistent.txt", files[0]])
+        .unwrap()
+        .collect();
+
+    assert_eq!(found.len(), 2);
+    assert_eq!(found[0].as_ref().unwrap_err().path, "test_matches_nonexistent.txt");
+    assert_eq!(found[1].as_ref().unwrap().line_number, 9);
+}
+
+#[test]
+fn test_json_flag() {
+    let input: &[u8] = b"Of Atreus,\nAgamemnon, King of men\xff\n";
+
+    let flags = Flags::new(&["--json", "-B1"]);
+
+    assert_eq!(
+        grep_reader("King|men", &flags, input).unwrap(),
+        vec![
+            r#"{"byte_offset":0,"line":"Of Atreus,","line_number":1,"path":"(standard input)","submatches":[],"type":"context"}"#,
+            concat!(
+                r#"{"byte_offset":11,"line":"Agamemnon, King of men"#,
+                "\u{fffd}",
+                r#"","line_number":2,"path":"(standard input)","#,
+                r#""submatches":[{"end":15,"start":11},{"end":22,"start":19}],"type":"match"}"#,
+            ),
+        ]
+    );
+}
+
+// Test coloring output
+
+#[test]
+fn test_color_flag_highlights_matches_file_names_and_line_numbers() {
+    let files = [
+        "test_color_flag_highlights_iliad.txt",
+        "test_color_flag_highlights_midsummer_night.txt",
+    ];
+
+    let test_fixture = Fixture::new(&files);
+
+    test_fixture.set_up();
+
+    let flags = Flags::new(&["--color=always", "-n", "-A1"]);
+
+    let expected = vec![
+        concat!(
+            "\x1b[35m\x1b[Ktest_color_flag_highlights_iliad.txt\x1b[m\x1b[K\x1b[36m\x1b[K:\x1b[m\x1b[K",
+            "\x1b[32m\x1b[K9\x1b[m\x1b[K\x1b[36m\x1b[K:\x1b[m\x1b[K",
+            "Of Atreus, \x1b[01;31m\x1b[KAgamemnon\x1b[m\x1b[K, King of \x1b[01;31m\x1b[Kmen\x1b[m\x1b[K.",
+        ),
+        "\x1b[36m\x1b[K--\x1b[m\x1b[K",
+        concat!(
+            "\x1b[35m\x1b[Ktest_color_flag_highlights_midsummer_night.txt\x1b[m\x1b[K\x1b[36m\x1b[K:\x1b[m\x1b[K",
+            "\x1b[32m\x1b[K7\x1b[m\x1b[K\x1b[36m\x1b[K:\x1b[m\x1b[K",
+            "If I refuse to wed De\x1b[01;31m\x1b[Kmet\x1b[m\x1b[Krius.",
+        ),
+    ];
+
+    assert_eq!(grep("Agamemnon|men|met", &flags, &files).unwrap(), expected);
+}
+
+#[test]
+fn test_color_flag_with_only_matching_and_count_flags() {
+    let flags = Flags::new(&["--color=always", "-o"]);
+
+    assert_eq!(
+        grep_reader("Achilles", &flags, ILIAD_CONTENT.as_bytes()).unwrap(),
+        vec!["\x1b[01;31m\x1b[KAchilles\x1b[m\x1b[K"; 2]
+    );
+
+    let flags = Flags::new(&["--color=always", "-c"]);
+
+    assert_eq!(grep_reader("Achilles", &flags, ILIAD_CONTENT.as_bytes()).unwrap(), vec!["2"]);
+}
+
+#[test]
+fn test_color_flag_auto_colors_only_terminals() {
+    let mut flags = Flags::new(&["--color"]);
+
+    assert_eq!(flags, Flags::new(&["--color=auto"]));
+    assert_eq!(
+        grep_reader("men", &flags, ILIAD_CONTENT.as_bytes()).unwrap(),
+        vec!["Of Atreus, Agamemnon, King of men."]
+    );
+
+    flags.resolve_color(true);
+
+    assert_eq!(flags, Flags::new(&["--color=always"]));
+
+    let mut flags = Flags::new(&["--color=auto"]);
+
+    flags.resolve_color(false);
+
+    assert_eq!(flags, Flags::new(&["--color=never"]));
+}
+
+// Test searching directories recursively
+
+/// Creates a directory tree for a test case, which is removed again when the fixture is dropped.
+/// The tree lives in the temporary directory, away from the `.gitignore` files of this crate.
+struct DirFixture {
+    root: PathBuf,
+}
+
+impl DirFixture {
+    fn new(test_case_name: &str, files: &[(&str, &[u8])]) -> Self {
+        let root = env::temp_dir().join(test_case_name);
+        for (file_name, file_content) in files {
+            let path = root.join(file_name);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(&path, file_content)
+                .unwrap_or_else(|_| panic!("Error setting up file '{}'", path.display()));
+        }
+        DirFixture { root }
+    }
+
+    fn path(&self, file_name: &str) -> String {
+        self.root.join(file_name).to_string_lossy().into_owned()
+    }
+}
+
+impl Drop for DirFixture {
+    fn drop(&mut self) {
+        fs::remove_dir_all(&self.root)
+            .unwrap_or_else(|_| panic!("Could not delete directory '{}'", self.root.display()));
+    }
+}
+
+#[test]
+fn test_recursive_flag_searches_directories() {
+    let fixture = DirFixture::new(
+        "test_recursive_flag_searches_directories",
+        &[
+            ("b/paradise_lost.txt", PARADISE_LOST_CONTENT.as_bytes()),
+            ("a/iliad.txt", ILIAD_CONTENT.as_bytes()),
+            ("a/deeper/midsummer_night.txt", MIDSUMMER_NIGHT_CONTENT.as_bytes()),
+        ],
+    );
+
+    let flags = Flags::new(&["-r", "-n"]);
+
+    let result = grep("Agamemnon|Forbidden|Demetrius", &flags, &[&fixture.path("")]);
+
+    assert_eq!(
+        result.unwrap(),
+        vec![
+            format!("{}:7:If I refuse to wed Demetrius.", fixture.path("a/deeper/midsummer_night.txt")),
+            format!("{}:9:Of Atreus, Agamemnon, King of men.", fixture.path("a/iliad.txt")),
+            format!("{}:2:Of that Forbidden Tree, whose mortal tast", fixture.path("b/paradise_lost.txt")),
+        ]
+    );
+}
+
+#[test]
+fn test_recursive_flag_with_include_exclude_and_exclude_dir_flags() {
+    let fixture = DirFixture::new(
+        "test_recursive_flag_with_include_exclude_and_exclude_dir_flags",
+        &[
+            ("iliad.txt", ILIAD_CONTENT.as_bytes()),
+            ("iliad.md", ILIAD_CONTENT.as_bytes()),
+            ("iliad.bak.txt", ILIAD_CONTENT.as_bytes()),
+            ("old/iliad.txt", ILIAD_CONTENT.as_bytes()),
+            ("new/iliad.txt", ILIAD_CONTENT.as_bytes()),
+        ],
+    );
+
+    let flags = Flags::new(&["-r", "--include=*.txt", "--exclude", "*.bak.*", "--exclude-dir=old"]);
+
+    let result = grep("Agamemnon", &flags, &[&fixture.path("")]);
+
+    assert_eq!(
+        result.unwrap(),
+        vec![
+            format!("{}:Of Atreus, Agamemnon, King of men.", fixture.path("iliad.txt")),
+            format!("{}:Of Atreus, Agamemnon, King of men.", fixture.path("new/iliad.txt")),
+        ]
+    );
+}
+
+#[test]
+fn test_recursive_flag_skips_gitignored_and_binary_files() {
+    let fixture = DirFixture::new(
+        "test_recursive_flag_skips_gitignored_and_binary_files",
+        &[
+            (".gitignore", b"target/\n*.log\n"),
+            ("iliad.txt", ILIAD_CONTENT.as_bytes()),
+            ("iliad.log", ILIAD_CONTENT.as_bytes()),
+            ("target/iliad.txt", ILIAD_CONTENT.as_bytes()),
+            ("iliad.bin", b"Agamemnon\0\x01\x02"),
+        ],
+    );
+
+    let flags = Flags::new(&["-r", "-l"]);
+
+    let result = grep("Agamemnon", &flags, &[&fixture.path("")]);
+
+    assert_eq!(result.unwrap(), vec![fixture.path("iliad.txt")]);
+}
+
+#[test]
+fn test_recursive_flag_ignores_gitignore_files_above_the_searched_directory() {
+    let fixture = DirFixture::new(
+        "test_recursive_flag_ignores_gitignore_files_above_the_searched_directory",
+        &[(".gitignore", b"*\n"), ("books/iliad.txt", ILIAD_CONTENT.as_bytes())],
+    );
+
+    let flags = Flags::new(&["-r", "-l"]);
+
+    let result = grep("Agamemnon", &flags, &[&fixture.path("books")]);
+
+    assert_eq!(result.unwrap(), vec![fixture.path("books/iliad.txt")]);
+}
+
+#[test]
+#[cfg(unix)]
+fn test_dereference_recursive_flag_follows_symbolic_links() {
+    let fixture = DirFixture::new(
+        "test_dereference_recursive_flag_follows_symbolic_links",
+        &[("library/iliad.txt", ILIAD_CONTENT.as_bytes()), ("shelf/.keep", b"")],
+    );
+    std::os::unix::fs::symlink(fixture.path("library"), fixture.path("shelf/library")).unwrap();
+    std::os::unix::fs::symlink(fixture.path("library/iliad.txt"), fixture.path("shelf/iliad.txt")).unwrap();
+
+    let result = grep("Agamemnon", &Flags::new(&["-r", "-l"]), &[&fixture.path("shelf")]);
+
+    assert_eq!(result.unwrap(), Vec::<String>::new());
+
+    let result = grep("Agamemnon", &Flags::new(&["-R", "-l"]), &[&fixture.path("shelf")]);
+
+    assert_eq!(result.unwrap(), vec![fixture.path("shelf/iliad.txt"), fixture.path("shelf/library/iliad.txt")]);
+}
+
+#[test]
+#[cfg(unix)]
+fn test_matches_yield_walk_errors_in_path_order() {
+    let fixture = DirFixture::new(
+        "test_matches_yield_walk_errors_in_path_order",
+        &[("a/iliad.txt", ILIAD_CONTENT.as_bytes()), ("c/iliad.txt", ILIAD_CONTENT.as_bytes())],
+    );
+    fs::create_dir(fixture.path("b")).unwrap();
+    std::os::unix::fs::symlink(fixture.path("nowhere"), fixture.path("b/broken")).unwrap();
+
+    let flags = Flags::new(&["-R"]);
+
+    let found: Vec<_> = matches("Agamemnon", &flags, &[&fixture.path("")]).unwrap().collect();
+
+    assert_eq!(found.len(), 3);
+    assert_eq!(found[0].as_ref().unwrap().path, fixture.path("a/iliad.txt"));
+    assert!(found[1].is_err());
+    assert_eq!(found[2].as_ref().unwrap().path, fixture.path("c/iliad.txt"));
+}
+
+#[test]
+fn test_directory_without_recursive_flag_returns_error() {
+    let fixture = DirFixture::new(
+        "test_directory_without_recursive_flag_returns_error",
+        &[("iliad.txt", ILIAD_CONTENT.as_bytes())],
+    );
+
+    let flags = Flags::new(&[]);
+
+    assert!(grep("Agamemnon", &flags, &[&fixture.path("")]).is_err());
+}
+
+// Test parsing command lines
+
+#[test]
+fn test_combined_short_flags() {
+    let args = Args::parse(&["-inA2", "Agamemnon", "iliad.txt"]).unwrap();
+
+    assert_eq!(args.patterns, vec!["Agamemnon"]);
+    assert_eq!(args.files, vec!["iliad.txt"]);
+    assert_eq!(args.flags, Flags::new(&["-i", "-n", "-A", "2"]));
+}
+
+#[test]
+fn test_long_flags_and_interleaved_operands() {
+    let args = Args::parse(&["Agamemnon", "--line-number", "iliad.txt", "--context", "1", "--include=*.txt"]).unwrap();
+
+    assert_eq!(args.patterns, vec!["Agamemnon"]);
+    assert_eq!(args.files, vec!["iliad.txt"]);
+    assert_eq!(args.flags, Flags::new(&["-n", "-C1", "--include", "*.txt"]));
+}
+
+#[test]
+fn test_terminator_makes_flags_operands() {
+    let args = Args::parse(&["-n", "--", "-v", "-"]).unwrap();
+
+    assert_eq!(args.patterns, vec!["-v"]);
+    assert_eq!(args.files, vec!["-"]);
+}
+
+#[test]
+fn test_regexp_and_file_flags_make_all_operands_files() {
+    let args = Args::parse(&["-e", "Agamemnon", "-eAchilles", "iliad.txt", "odyssey.txt"]).unwrap();
+
+    assert_eq!(args.patterns, vec!["Agamemnon", "Achilles"]);
+    assert_eq!(args.files, vec!["iliad.txt", "odyssey.txt"]);
+
+    let args = Args::parse(&["-f", "patterns", "iliad.txt"]).unwrap();
+
+    assert!(args.patterns.is_empty());
+    assert_eq!(args.files, vec!["iliad.txt"]);
+    assert_eq!(args.flags, Flags::new(&["--file=patterns"]));
+}
+
+#[test]
+fn test_invalid_command_lines_return_errors() {
+    assert_eq!(Args::parse(&["-q", "x"]).unwrap_err(), ArgsError::UnknownFlag("-q".to_string()));
+    assert_eq!(
+        Args::parse(&["--quiet", "x"]).unwrap_err(),
+        ArgsError::UnknownFlag("--quiet".to_string())
+    );
+    assert_eq!(Args::parse(&["x", "-A"]).unwrap_err(), ArgsError::MissingArgument("-A".to_string()));
+    assert_eq!(
+        Args::parse(&["-C", "many", "x"]).unwrap_err(),
+        ArgsError::InvalidArgument { flag: "-C".to_string(), value: "many".to_string() }
+    );
+    assert_eq!(
+        Args::parse(&["--color=sometimes", "x"]).unwrap_err(),
+        ArgsError::InvalidArgument { flag: "--color".to_string(), value: "sometimes".to_string() }
+    );
+    assert_eq!(
+        Args::parse(&["--invert-match=yes", "x"]).unwrap_err(),
+        ArgsError::UnexpectedArgument("--invert-match".to_string())
+    );
+    assert_eq!(Args::parse(&["-n"]).unwrap_err(), ArgsError::MissingPattern);
+    assert_eq!(
+        Flags::parse(&["-n", "iliad.txt"]).unwrap_err(),
+        ArgsError::UnexpectedOperand("iliad.txt".to_string())
+    );
+    assert_eq!(
+        Args::parse(&["-c", "--json", "x"]).unwrap_err(),
+        ArgsError::ConflictingFlags("--json".to_string(), "--count".to_string())
+    );
+    assert_eq!(
+        Flags::parse(&["--json", "-L"]).unwrap_err(),
+        ArgsError::ConflictingFlags("--json".to_string(), "--files-without-match".to_string())
+    );
+}
+
+// Test the grep binary
+
+fn run_grep(args: &[&str]) -> (Option<i32>, String, String) {
+    let output = Command::new(env!("CARGO_BIN_EXE_grep")).args(args).output().unwrap();
+    (
+        output.status.code(),
+        String::from_utf8(output.stdout).unwrap(),
+        String::from_utf8(output.stderr).unwrap(),
+    )
+}
+
+#[test]
+fn test_binary_exit_codes() {
+    let files = ["test_binary_exit_codes_iliad.txt"];
+
+    let test_fixture = Fixture::new(&files);
+
+    test_fixture.set_up();
+
+    assert_eq!(
+        run_grep(&["-n", "Agamemnon", files[0]]),
+        (Some(0), "9:Of Atreus, Agamemnon, King of men.\n".to_string(), String::new())
+    );
+    assert_eq!(run_grep(&["Gandalf", files[0]]), (Some(1), String::new(), String::new()));
+
+    let (status, _, stderr) = run_grep(&["--frodo", "Gandalf", files[0]]);
+    assert_eq!(status, Some(2));
+    assert!(stderr.starts_with("grep: unrecognized option '--frodo'"));
+}
+
+#[test]
+fn test_binary_reports_missing_files_and_goes_on() {
+    let files = ["test_binary_reports_missing_files_and_goes_on_iliad.txt"];
+
+    let test_fixture = Fixture::new(&files);
+
+    test_fixture.set_up();
+
+    let (status, stdout, stderr) = run_grep(&["-l", "Agamemnon", "test_binary_missing.txt", files[0]]);
+    assert_eq!(status, Some(2));
+    assert_eq!(stdout, format!("{}\n", files[0]));
+    assert!(stderr.starts_with("grep: test_binary_missing.txt: "));
+}
+
+#[test]
+fn test_binary_with_several_patterns() {
+    let files = ["test_binary_with_several_patterns_iliad.txt"];
+
+    let test_fixture = Fixture::new(&files);
+
+    test_fixture.set_up();
+
+    assert_eq!(
+        run_grep(&["-n", "-e", "Agamemnon", "-e", "Goddess", files[0]]),
+        (
+            Some(0),
+            "1:Achilles sing, O Goddess! Peleus' son;
+9:Of Atreus, Agamemnon, King of men.
+".to_string(),
+            String::new()
+        )
+    );
+}
+
+#[test]
+fn test_binary_colors_output_to_pipes_only_when_forced() {
+    let files = ["test_binary_colors_output_to_pipes_only_when_forced_iliad.txt"];
+
+    let test_fixture = Fixture::new(&files);
+
+    test_fixture.set_up();
+
+    assert_eq!(
+        run_grep(&["--color=auto", "Agamemnon", files[0]]),
+        (Some(0), "Of Atreus, Agamemnon, King of men.\n".to_string(), String::new())
+    );
+    assert_eq!(
+        run_grep(&["--color=always", "Agamemnon", files[0]]),
+        (
+            Some(0),
+            "Of Atreus, \x1b[01;31m\x1b[KAgamemnon\x1b[m\x1b[K, King of men.\n".to_string(),
+            String::new()
+        )
+    );
+}
@@ -0,0 +1,50 @@
+#![feature(test)]
+extern crate poker;
+extern crate test;
+
+use poker::eval::{self, CardCode};
+use poker::{Card, Hand};
+use test::Bencher;
+
+/// The number of hands ranked in each iteration, so that ns/iter divided by it is the time per hand.
+const HANDS: usize = 1000;
+
+#[bench]
+fn bench_lookup_rank_five_cards(b: &mut Bencher) {
+    let hands: Vec<[CardCode; 5]> = deals::<5>().collect();
+    eval::rank(hands[0]);
+    b.iter(|| hands.iter().map(|&hand| eval::rank(hand) as u32).sum::<u32>());
+}
+
+#[bench]
+fn bench_lookup_best_rank_seven_cards(b: &mut Bencher) {
+    let hands: Vec<[CardCode; 7]> = deals::<7>().collect();
+    b.iter(|| hands.iter().map(|hand| eval::best_rank(hand) as u32).sum::<u32>());
+}
+
+#[bench]
+fn bench_hand_score_five_cards(b: &mut Bencher) {
+    let hands: Vec<Vec<Card>> = deals::<5>().map(|hand| hand.iter().map(|&code| Card::from(code)).collect()).collect();
+    b.iter(|| hands.iter().map(|hand| Hand::new(hand).unwrap()).max());
+}
+
+/// `HANDS` hands of `N` distinct cards, the same on every run.
+fn deals<const N: usize>() -> impl Iterator<Item = [CardCode; N]> {
+    let mut state: u64 = 0x2545_f491_4f6c_dd1d;
+    (0..HANDS).map(move |_| {
+        let mut hand = [CardCode::new(0).unwrap(); N];
+        let mut dealt = 0;
+        while dealt < N {
+            // xorshift64
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            let card = CardCode::new((state % 52) as u8).unwrap();
+            if !hand[..dealt].contains(&card) {
+                hand[dealt] = card;
+                dealt += 1;
+            }
+        }
+        hand
+    })
+}
@@ -0,0 +1,146 @@
+use std::error;
+use std::fmt;
+use std::str::FromStr;
+
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum Suit {
+    Heart,
+    Diamond,
+    Club,
+    Spade,
+    /// The suit of jokers alone.
+    Joker,
+}
+
+/// The suits in the order card codes and new decks use.
+pub(crate) const SUITS: [Suit; 4] = [Suit::Heart, Suit::Diamond, Suit::Club, Suit::Spade];
+
+/// A playing card, with ranks from 2 to 14 for an ace, or a joker, with rank 0.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub struct Card {
+    pub(crate) rank: u8,
+    pub(crate) suit: Suit,
+}
+
+impl Card {
+    pub const JOKER: Card = Card { rank: 0, suit: Suit::Joker };
+
+    pub fn is_joker(&self) -> bool {
+        self.suit == Suit::Joker
+    }
+
+    pub fn rank(&self) -> u8 {
+        self.rank
+    }
+
+    pub fn suit(&self) -> Suit {
+        self.suit
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum ParseError {
+    InvalidRank(String),
+    InvalidSuit(String),
+    WrongCardCount(usize),
+    DuplicateCard(Card),
+    /// Too many cards dealt to players for the board to be completed from the rest of the deck.
+    TooManyCards(usize),
+    /// A joker where only the cards of a standard deck are played.
+    UnexpectedJoker,
+}
+
+impl Suit {
+    fn letter(self) -> &'static str {
+        match self {
+            Suit::Heart => "H",
+            Suit::Diamond => "D",
+            Suit::Club => "C",
+            Suit::Spade => "S",
+            Suit::Joker => "K",
+        }
+    }
+
+    fn symbol(self) -> &'static str {
+        match self {
+            Suit::Heart => "♥",
+            Suit::Diamond => "♦",
+            Suit::Club => "♣",
+            Suit::Spade => "♠",
+            Suit::Joker => "K",
+        }
+    }
+}
+
+/// Writes the letter of a suit, or with `{:#}` its symbol, like `♥`.
+impl fmt::Display for Suit {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(if f.alternate() { self.symbol() } else { self.letter() })
+    }
+}
+
+/// Writes a card as the parser reads it, like `10H`, `QS` or `JK`, or with `{:#}` with the symbol
+/// of its suit, like `10♥`.
+impl fmt::Display for Card {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let rank = match self.rank {
+            0 => return f.write_str("JK"),
+            11 => "J".to_string(),
+            12 => "Q".to_string(),
+            13 => "K".to_string(),
+            14 => "A".to_string(),
+            rank => rank.to_string(),
+        };
+        let suit = if f.alternate() { self.suit.symbol() } else { self.suit.letter() };
+        write!(f, "{}{}", rank, suit)
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseError::InvalidRank(card) => write!(f, "invalid rank in card '{}'", card),
+            ParseError::InvalidSuit(card) => write!(f, "invalid suit in card '{}'", card),
+            ParseError::WrongCardCount(count) => write!(f, "wrong number of cards: {}", count),
+            ParseError::DuplicateCard(card) => write!(f, "duplicate card {}", card),
+            ParseError::TooManyCards(count) => write!(f, "too many cards dealt to complete the board: {}", count),
+            ParseError::UnexpectedJoker => write!(f, "jokers are not played here"),
+        }
+    }
+}
+
+impl error::Error for ParseError {}
+
+/// Parses a card written as its rank, `2` to `10` or `T`, `J`, `Q`, `K` or `A`, followed by its
+/// suit, `H`, `D`, `C` or `S` or their symbols, like `10H`, `TH`, `10♥` or `QS`, or a joker
+/// written `JK`.
+impl FromStr for Card {
+    type Err = ParseError;
+
+    fn from_str(card: &str) -> Result<Self, Self::Err> {
+        use Suit::*;
+        if card == "JK" {
+            return Ok(Card::JOKER);
+        }
+        let mut chars = card.chars();
+        let suit = match chars.next_back() {
+            Some('H' | '♥' | '♡') => Heart,
+            Some('D' | '♦' | '♢') => Diamond,
+            Some('C' | '♣' | '♧') => Club,
+            Some('S' | '♠' | '♤') => Spade,
+            _ => return Err(ParseError::InvalidSuit(card.to_string())),
+        };
+        let rank = match chars.as_str() {
+            "T" => 10,
+            "J" => 11,
+            "Q" => 12,
+            "K" => 13,
+            "A" => 14,
+            rank => match rank.parse() {
+                Ok(value @ 2..=10) if !rank.starts_with(['+', '0']) => value,
+                _ => return Err(ParseError::InvalidRank(card.to_string())),
+            },
+        };
+        Ok(Card { rank, suit })
+    }
+}
@@ -0,0 +1,111 @@
+use crate::card::{Card, SUITS};
+use itertools::Itertools;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use std::convert::TryInto;
+
+/// The cards left to deal, from the top of the deck.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Deck {
+    cards: Vec<Card>,
+    /// The number of cards dealt or burnt from the top.
+    dealt: usize,
+}
+
+impl Default for Deck {
+    fn default() -> Self {
+        Deck::new()
+    }
+}
+
+impl Deck {
+    /// A full deck ordered by rank from the deuces, then by suit: hearts, diamonds, clubs and
+    /// spades.
+    pub fn new() -> Deck {
+        let cards = (2..=14)
+            .flat_map(|rank| SUITS.iter().map(move |&suit| Card { rank, suit }))
+            .collect();
+        Deck { cards, dealt: 0 }
+    }
+
+    /// A full deck shuffled by `shuffle`.
+    pub fn shuffled(seed: u64) -> Deck {
+        let mut deck = Deck::new();
+        deck.shuffle(seed);
+        deck
+    }
+
+    /// Shuffles the cards left. The same seed always puts the same cards in the same order.
+    pub fn shuffle(&mut self, seed: u64) {
+        self.shuffle_with(&mut ChaCha8Rng::seed_from_u64(seed));
+    }
+
+    /// Shuffles the cards left with a random number generator of one's own, to draw many shuffles
+    /// from a single seed.
+    pub fn shuffle_with<R: Rng + ?Sized>(&mut self, rng: &mut R) {
+        self.cards[self.dealt..].shuffle(rng);
+    }
+
+    /// The cards left, from the top.
+    pub fn cards(&self) -> &[Card] {
+        &self.cards[self.dealt..]
+    }
+
+    pub fn len(&self) -> usize {
+        self.cards().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cards().is_empty()
+    }
+
+    /// Deals the top card, if any is left.
+    pub fn deal(&mut self) -> Option<Card> {
+        let card = self.cards().first().copied()?;
+        self.dealt += 1;
+        Some(card)
+    }
+
+    /// Deals `count` cards from the top, or none if fewer are left.
+    pub fn deal_cards(&mut self, count: usize) -> Option<Vec<Card>> {
+        let cards = self.cards().get(..count)?.to_vec();
+        self.dealt += count;
+        Some(cards)
+    }
+
+    /// Deals `N` cards to each of `players`, one at a time around the table, or none if too few
+    /// are left.
+    pub fn deal_hands<const N: usize>(&mut self, players: usize) -> Option<Vec<[Card; N]>> {
+        let cards = self.deal_cards(players.checked_mul(N)?)?;
+        let hands = (0..players)
+            .map(|player| {
+                let hand: Vec<Card> = cards.iter().skip(player).step_by(players).copied().collect();
+                hand.try_into().unwrap()
+            })
+            .collect();
+        Some(hands)
+    }
+
+    /// Discards the top card, as before dealing the flop, the turn and the river.
+    pub fn burn(&mut self) -> Option<Card> {
+        self.deal()
+    }
+
+    /// Takes a card out of play wherever it is among the cards left, returning whether it was
+    /// there.
+    pub fn remove(&mut self, card: Card) -> bool {
+        match self.cards().iter().position(|&left| left == card) {
+            Some(i) => {
+                self.cards.remove(self.dealt + i);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Every way to pick `count` of the cards left, in the order of the deck.
+    pub fn combinations(&self, count: usize) -> impl Iterator<Item = Vec<Card>> + '_ {
+        self.cards().iter().copied().combinations(count)
+    }
+}
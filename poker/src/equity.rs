@@ -0,0 +1,170 @@
+//! How often Hold'em hands win against each other, by dealing every possible rest of the board or
+//! a random sample of them.
+
+use crate::eval::{self, CardCode};
+use crate::{check_distinct, Card, Deck, ParseError};
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use std::convert::TryFrom;
+
+/// How a player fares over the boards dealt.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Equity {
+    pub wins: u64,
+    /// The boards where the player splits the pot with others.
+    pub ties: u64,
+    pub losses: u64,
+    /// The pots won, a pot split between several players counting as a share of one.
+    pub pots: f64,
+}
+
+impl Equity {
+    pub fn boards(&self) -> u64 {
+        self.wins + self.ties + self.losses
+    }
+
+    /// The share of the pots the player wins on average, from 0 to 1, or 0 when no board was
+    /// dealt.
+    pub fn equity(&self) -> f64 {
+        self.per_board(self.pots)
+    }
+
+    pub fn win_rate(&self) -> f64 {
+        self.per_board(self.wins as f64)
+    }
+
+    pub fn tie_rate(&self) -> f64 {
+        self.per_board(self.ties as f64)
+    }
+
+    fn per_board(&self, count: f64) -> f64 {
+        match self.boards() {
+            0 => 0.0,
+            boards => count / boards as f64,
+        }
+    }
+}
+
+/// The most boards `calculate` deals exhaustively. There are 1,712,304 before the flop.
+pub const EXHAUSTIVE_LIMIT: u64 = 100_000;
+
+/// Computes the equity of each player, exhaustively when there are at most `EXHAUSTIVE_LIMIT`
+/// ways to complete the board, and otherwise over `trials` random boards drawn from `seed`.
+pub fn calculate(players: &[[Card; 2]], board: &[Card], trials: u64, seed: u64) -> Result<Vec<Equity>, ParseError> {
+    let deal = Deal::new(players, board)?;
+    if deal.boards() <= EXHAUSTIVE_LIMIT {
+        Ok(deal.exhaustive())
+    } else {
+        Ok(deal.monte_carlo(trials, seed))
+    }
+}
+
+/// Computes the equity of each player over every way to complete the board.
+pub fn exhaustive(players: &[[Card; 2]], board: &[Card]) -> Result<Vec<Equity>, ParseError> {
+    Ok(Deal::new(players, board)?.exhaustive())
+}
+
+/// Computes the equity of each player over `trials` boards completed at random. The same seed
+/// always deals the same boards.
+pub fn monte_carlo(players: &[[Card; 2]], board: &[Card], trials: u64, seed: u64) -> Result<Vec<Equity>, ParseError> {
+    Ok(Deal::new(players, board)?.monte_carlo(trials, seed))
+}
+
+/// The cards known so far, and those left in the deck.
+struct Deal {
+    players: Vec<[CardCode; 2]>,
+    board: Vec<CardCode>,
+    deck: Vec<CardCode>,
+}
+
+impl Deal {
+    fn new(players: &[[Card; 2]], board: &[Card]) -> Result<Deal, ParseError> {
+        if board.len() > 5 {
+            return Err(ParseError::WrongCardCount(board.len()));
+        }
+        let known: Vec<Card> = players.iter().flatten().chain(board).copied().collect();
+        check_distinct(&known)?;
+        let code = CardCode::try_from;
+        let players = players.iter()
+            .map(|&[a, b]| Ok([code(a)?, code(b)?]))
+            .collect::<Result<Vec<_>, ParseError>>()?;
+        let board = board.iter().map(|&card| code(card)).collect::<Result<Vec<_>, _>>()?;
+        let mut deck = Deck::new();
+        for &card in known.iter() {
+            deck.remove(card);
+        }
+        if deck.len() < 5 - board.len() {
+            return Err(ParseError::TooManyCards(known.len()));
+        }
+        Ok(Deal {
+            players,
+            board,
+            deck: deck.cards().iter().map(|&card| code(card).expect("decks have no jokers")).collect(),
+        })
+    }
+
+    /// The number of ways to complete the board.
+    fn boards(&self) -> u64 {
+        let missing = 5 - self.board.len() as u64;
+        let left = self.deck.len() as u64;
+        (0..missing).fold(1, |boards, i| boards * (left - i) / (i + 1))
+    }
+
+    fn exhaustive(&self) -> Vec<Equity> {
+        let mut equities = vec![Equity::default(); self.players.len()];
+        let mut board = [CardCode::new(0).unwrap(); 5];
+        board[..self.board.len()].copy_from_slice(&self.board);
+        self.complete(&mut board, self.board.len(), 0, &mut equities);
+        equities
+    }
+
+    /// Deals every combination of the cards of the deck from `next` on to the board from `dealt`
+    /// on.
+    fn complete(&self, board: &mut [CardCode; 5], dealt: usize, next: usize, equities: &mut [Equity]) {
+        if dealt == 5 {
+            self.showdown(board, equities);
+            return;
+        }
+        for i in next..self.deck.len() {
+            board[dealt] = self.deck[i];
+            self.complete(board, dealt + 1, i + 1, equities);
+        }
+    }
+
+    fn monte_carlo(&self, trials: u64, seed: u64) -> Vec<Equity> {
+        let mut equities = vec![Equity::default(); self.players.len()];
+        let mut rng = ChaCha8Rng::seed_from_u64(seed);
+        let mut deck = self.deck.clone();
+        let mut board = [CardCode::new(0).unwrap(); 5];
+        board[..self.board.len()].copy_from_slice(&self.board);
+        for _ in 0..trials {
+            // Draws the missing cards as the first steps of a Fisher-Yates shuffle would.
+            for (i, dealt) in (self.board.len()..5).enumerate() {
+                let j = rng.gen_range(i..deck.len());
+                deck.swap(i, j);
+                board[dealt] = deck[i];
+            }
+            self.showdown(&board, &mut equities);
+        }
+        equities
+    }
+
+    fn showdown(&self, board: &[CardCode; 5], equities: &mut [Equity]) {
+        let ranks: Vec<u16> = self.players.iter()
+            .map(|&[a, b]| eval::best_rank(&[a, b, board[0], board[1], board[2], board[3], board[4]]))
+            .collect();
+        let best = ranks.iter().copied().max().unwrap_or(0);
+        let winners = ranks.iter().filter(|&&rank| rank == best).count();
+        for (equity, &rank) in equities.iter_mut().zip(ranks.iter()) {
+            if rank < best {
+                equity.losses += 1;
+            } else if winners == 1 {
+                equity.wins += 1;
+                equity.pots += 1.0;
+            } else {
+                equity.ties += 1;
+                equity.pots += 1.0 / winners as f64;
+            }
+        }
+    }
+}
@@ -0,0 +1,211 @@
+//! A fast evaluator for simulations, where cards are small integers and hands are ranked by table
+//! lookups, without allocating.
+
+use crate::card::SUITS;
+use crate::{Card, ParseError, Poker, Suit};
+use std::collections::BTreeSet;
+use std::convert::TryFrom;
+use std::iter;
+use std::sync::OnceLock;
+
+/// A card encoded as a number from 0 to 51: four times its rank, from 0 for a deuce to 12 for an
+/// ace, plus its suit.
+#[derive(Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Clone, Copy)]
+pub struct CardCode(u8);
+
+impl CardCode {
+    pub fn new(code: u8) -> Option<CardCode> {
+        if code < 52 {
+            Some(CardCode(code))
+        } else {
+            None
+        }
+    }
+
+    pub fn code(self) -> u8 {
+        self.0
+    }
+
+    fn rank_index(self) -> usize {
+        (self.0 >> 2) as usize
+    }
+
+    fn suit_index(self) -> u8 {
+        self.0 & 3
+    }
+}
+
+/// Fails on a joker, which has no code.
+impl TryFrom<Card> for CardCode {
+    type Error = ParseError;
+
+    fn try_from(card: Card) -> Result<Self, Self::Error> {
+        match SUITS.iter().position(|&suit| suit == card.suit) {
+            Some(suit) => Ok(CardCode((card.rank - 2) * 4 + suit as u8)),
+            None => Err(ParseError::UnexpectedJoker),
+        }
+    }
+}
+
+impl From<CardCode> for Card {
+    fn from(code: CardCode) -> Self {
+        Card {
+            rank: code.rank_index() as u8 + 2,
+            suit: SUITS[code.suit_index() as usize],
+        }
+    }
+}
+
+/// A key for each rank such that the sums of the keys of five cards are different for all the
+/// multisets of ranks five cards can have. Each is the smallest keeping the sums distinct given the
+/// keys of lower ranks, which keeps the largest sum, and so the lookup table, small.
+const RANK_KEYS: [u32; 13] = [0, 1, 5, 22, 94, 312, 992, 2422, 5624, 12522, 19998, 43258, 79415];
+
+/// The largest sum of rank keys, for four aces and a king.
+const MAX_RANK_SUM: usize = (4 * RANK_KEYS[12] + RANK_KEYS[11]) as usize;
+
+/// The number of distinct ranks, from 1 to 7462.
+pub const RANKS: u16 = 7462;
+
+struct Tables {
+    /// The ranks of flushes, by the bit mask of their five ranks.
+    flushes: Vec<u16>,
+    /// The ranks of the other hands, by the sum of the keys of their ranks.
+    others: Vec<u16>,
+    /// The score of each rank, the first one being unused.
+    scores: Vec<Poker>,
+}
+
+impl Tables {
+    /// Scores one hand for every flush and every multiset of ranks, and numbers the scores.
+    fn new() -> Tables {
+        let mut flushes = Vec::new();
+        let mut others = Vec::new();
+        for mask in 0u16..1 << 13 {
+            if mask.count_ones() == 5 {
+                let cards: Vec<Card> = (0..13)
+                    .filter(|rank| mask & 1 << rank != 0)
+                    .map(|rank| Card { rank: rank + 2, suit: Suit::Spade })
+                    .collect();
+                flushes.push((mask, Poker::new(&cards)));
+            }
+        }
+        let mut counts = [0u8; 13];
+        rank_multisets(&mut counts, 0, 5, &mut |counts| {
+            // Dealing suits in turn never makes a flush out of several ranks.
+            let cards: Vec<Card> = counts.iter()
+                .enumerate()
+                .flat_map(|(rank, &count)| (0..count).map(move |_| rank as u8 + 2))
+                .zip(SUITS.iter().cycle())
+                .map(|(rank, &suit)| Card { rank, suit })
+                .collect();
+            let sum: u32 = counts.iter().zip(RANK_KEYS.iter()).map(|(&count, key)| count as u32 * key).sum();
+            others.push((sum, Poker::new(&cards)));
+        });
+
+        let scores: BTreeSet<Poker> = flushes.iter()
+            .map(|&(_, score)| score)
+            .chain(others.iter().map(|&(_, score)| score))
+            .collect();
+        // Ranks start from 1, after a placeholder lower than any score.
+        let scores: Vec<Poker> = iter::once(Poker::HighCard(0, 0, 0, 0, 0)).chain(scores).collect();
+        let rank = |score: &Poker| scores.binary_search(score).unwrap() as u16;
+        let mut tables = Tables {
+            flushes: vec![0; 1 << 13],
+            others: vec![0; MAX_RANK_SUM + 1],
+            scores: Vec::new(),
+        };
+        for (mask, score) in flushes.iter() {
+            tables.flushes[*mask as usize] = rank(score);
+        }
+        for (sum, score) in others.iter() {
+            tables.others[*sum as usize] = rank(score);
+        }
+        tables.scores = scores;
+        tables
+    }
+}
+
+/// Calls `f` with the count of each rank, for every way of having `left` more cards of the ranks
+/// from `rank` on, at most four of each.
+fn rank_multisets(counts: &mut [u8; 13], rank: usize, left: u8, f: &mut dyn FnMut(&[u8; 13])) {
+    if rank == 13 {
+        if left == 0 {
+            f(counts);
+        }
+        return;
+    }
+    for count in 0..=left.min(4) {
+        counts[rank] = count;
+        rank_multisets(counts, rank + 1, left - count, f);
+    }
+    counts[rank] = 0;
+}
+
+fn tables() -> &'static Tables {
+    static TABLES: OnceLock<Tables> = OnceLock::new();
+    TABLES.get_or_init(Tables::new)
+}
+
+/// Ranks five distinct cards from 1, for 7 5 4 3 2 of several suits, to `RANKS`, for a royal
+/// flush. Hands have the same rank exactly when they have the same score, and higher ranks are
+/// better hands.
+///
+/// The tables are built on the first call.
+///
+/// # Panics
+///
+/// In debug builds, if the cards are not distinct. Release builds leave them unchecked for speed,
+/// and rank repeated cards like another hand or as 0.
+pub fn rank(cards: [CardCode; 5]) -> u16 {
+    rank_in(tables(), cards)
+}
+
+fn rank_in(tables: &Tables, cards: [CardCode; 5]) -> u16 {
+    debug_assert!(
+        cards.iter().enumerate().all(|(i, card)| !cards[i + 1..].contains(card)),
+        "cards must be distinct: {:?}",
+        cards
+    );
+    let suit = cards[0].suit_index();
+    if cards.iter().all(|card| card.suit_index() == suit) {
+        let mask = cards.iter().fold(0, |mask, card| mask | 1 << card.rank_index());
+        tables.flushes[mask]
+    } else {
+        let sum: u32 = cards.iter().map(|card| RANK_KEYS[card.rank_index()]).sum();
+        // Repeated cards may add up past the table.
+        tables.others.get(sum as usize).copied().unwrap_or(0)
+    }
+}
+
+/// The rank of the best five cards out of five to seven distinct cards, as in Hold'em.
+///
+/// # Panics
+///
+/// If there are fewer than five or more than seven cards, and like `rank` on repeated cards.
+pub fn best_rank(cards: &[CardCode]) -> u16 {
+    assert!((5..=7).contains(&cards.len()), "five to seven cards are needed");
+    let tables = tables();
+    let n = cards.len();
+    let mut best = 0;
+    for a in 0..n {
+        for b in a + 1..n {
+            for c in b + 1..n {
+                for d in c + 1..n {
+                    for e in d + 1..n {
+                        best = best.max(rank_in(tables, [cards[a], cards[b], cards[c], cards[d], cards[e]]));
+                    }
+                }
+            }
+        }
+    }
+    best
+}
+
+/// The score of the hands of a rank, if it is one from 1 to `RANKS`.
+pub fn score(rank: u16) -> Option<Poker> {
+    match rank {
+        1..=RANKS => Some(tables().scores[rank as usize]),
+        _ => None,
+    }
+}
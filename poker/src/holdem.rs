@@ -0,0 +1,36 @@
+//! Texas Hold'em showdowns, where each player makes the best five-card hand out of their two hole
+//! cards and the community cards on the board.
+
+use crate::{check_distinct, Card, Hand, ParseError};
+use itertools::Itertools;
+
+/// Returns the best five-card hand out of two hole cards and three to five community cards.
+pub fn best_hand(hole: [Card; 2], board: &[Card]) -> Result<Hand, ParseError> {
+    let cards: Vec<Card> = hole.iter().chain(board).copied().collect();
+    if !(5..=7).contains(&cards.len()) {
+        return Err(ParseError::WrongCardCount(cards.len()));
+    }
+    check_distinct(&cards)?;
+    let best = cards.into_iter()
+        .combinations(5)
+        .map(|cards| Hand::new(&cards))
+        .collect::<Result<Vec<Hand>, _>>()?
+        .into_iter()
+        .max();
+    Ok(best.expect("there is a hand of five cards at least"))
+}
+
+/// Returns the indexes of the players whose hole cards make the best hand with the board, several
+/// when they split the pot.
+pub fn showdown(players: &[[Card; 2]], board: &[Card]) -> Result<Vec<usize>, ParseError> {
+    let all: Vec<Card> = players.iter().flatten().chain(board).copied().collect();
+    check_distinct(&all)?;
+    let hands = players.iter()
+        .map(|&hole| best_hand(hole, board))
+        .collect::<Result<Vec<Hand>, _>>()?;
+    let best = match hands.iter().max() {
+        Some(best) => best,
+        None => return Ok(Vec::new()),
+    };
+    Ok(hands.iter().positions(|hand| hand == best).collect())
+}
@@ -1,177 +1,290 @@
-#[macro_use]
-extern crate lazy_static;
+mod card;
+mod deck;
+pub mod equity;
+pub mod eval;
+pub mod holdem;
+pub mod lowball;
+pub mod omaha;
+pub mod stud;
 
-#[derive(Debug)]
-struct Hand<'a> {
+pub use crate::card::{Card, ParseError, Suit};
+pub use crate::deck::Deck;
+
+/// Five cards, distinct but for jokers.
+#[derive(Debug, Clone)]
+pub struct Hand {
+    cards: Vec<Card>,
     score: Poker,
-    str: &'a str,
 }
 
-impl<'a> PartialEq for Hand<'a> {
+/// Hands compare by their score alone, whatever their suits.
+impl PartialEq for Hand {
     fn eq(&self, other: &Self) -> bool {
         self.score == other.score
     }
 }
 
-impl<'a> PartialOrd for Hand<'a> {
+impl Eq for Hand {}
+
+impl PartialOrd for Hand {
     fn partial_cmp(&self, other: &Hand) -> Option<Ordering> {
-        self.score.partial_cmp(&other.score)
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Hand {
+    fn cmp(&self, other: &Hand) -> Ordering {
+        self.score.cmp(&other.score)
     }
 }
 
-#[derive(Debug, PartialOrd, PartialEq)]
-enum Poker {
-    HighSeven(u8, u8, u8, u8, u8),
-    Pair(u8),
+/// The category of a hand with every rank needed to break ties within it, from the most to the
+/// least significant. Categories are declared from the lowest to the highest, so the derived order
+/// is the order of poker hands. Straights are known by their highest card, five for the wheel
+/// `A 2 3 4 5`. Only wild cards make five of a kind.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Hash)]
+pub enum Poker {
+    HighCard(u8, u8, u8, u8, u8),
+    /// The pair, then the three kickers.
+    Pair(u8, u8, u8, u8),
+    /// The higher pair, the lower pair, then the kicker.
     TwoPairs(u8, u8, u8),
+    /// The three of a kind, then the two kickers.
     ThreeOfAKind(u8, u8, u8),
     Straight(u8),
-    Flush(u8),
+    Flush(u8, u8, u8, u8, u8),
+    /// The three of a kind, then the pair.
     FullHouse(u8, u8),
+    /// The four of a kind, then the kicker.
     FourOfAKind(u8, u8),
     StraightFlush(u8),
+    FiveOfAKind(u8),
 }
 
-#[derive(Debug, PartialEq, Clone, Copy)]
-enum Suit {
-    Heart,
-    Diamond,
-    Club,
-    Spade,
+/// The name of a rank, aces being 1 or 14. Ranks no card has, which the fields of `Poker` can
+/// still hold, are written as numbers.
+fn rank_name(rank: u8) -> String {
+    const NAMES: [&str; 13] =
+        ["Two", "Three", "Four", "Five", "Six", "Seven", "Eight", "Nine", "Ten", "Jack", "Queen", "King", "Ace"];
+    match rank {
+        1 => "Ace".to_string(),
+        2..=14 => NAMES[rank as usize - 2].to_string(),
+        rank => rank.to_string(),
+    }
 }
 
-#[derive(Debug)]
-struct Card {
-    rank: u8,
-    suit: Suit,
+fn plural_rank_name(rank: u8) -> String {
+    match rank {
+        6 => "Sixes".to_string(),
+        rank => format!("{}s", rank_name(rank)),
+    }
 }
 
-use std::collections::HashMap;
-
-fn frequencies(vs: &[u8]) -> HashMap<u8, u8> {
-    vs.iter().fold(HashMap::new(), |mut acc, &value| {
-        *acc.entry(value).or_insert(0) += 1;
-        acc
-    })
+/// Describes a hand by its category and the ranks that make it, like `Full house, Kings over
+/// Sevens`. Kickers are left out.
+impl fmt::Display for Poker {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use Poker::*;
+        match *self {
+            HighCard(high, ..) => write!(f, "High card, {}", rank_name(high)),
+            Pair(pair, ..) => write!(f, "Pair of {}", plural_rank_name(pair)),
+            TwoPairs(high, low, _) => {
+                write!(f, "Two pairs, {} and {}", plural_rank_name(high), plural_rank_name(low))
+            }
+            ThreeOfAKind(three, ..) => write!(f, "Three of a kind, {}", plural_rank_name(three)),
+            Straight(5) => write!(f, "Straight, Five high (wheel)"),
+            Straight(high) => write!(f, "Straight, {} high", rank_name(high)),
+            Flush(high, ..) => write!(f, "Flush, {} high", rank_name(high)),
+            FullHouse(three, pair) => {
+                write!(f, "Full house, {} over {}", plural_rank_name(three), plural_rank_name(pair))
+            }
+            FourOfAKind(four, _) => write!(f, "Four of a kind, {}", plural_rank_name(four)),
+            StraightFlush(14) => write!(f, "Royal flush"),
+            StraightFlush(5) => write!(f, "Straight flush, Five high (wheel)"),
+            StraightFlush(high) => write!(f, "Straight flush, {} high", rank_name(high)),
+            FiveOfAKind(five) => write!(f, "Five of a kind, {}", plural_rank_name(five)),
+        }
+    }
 }
 
+/// The highest card of a straight, given ranks sorted from the highest.
 fn is_straight(ranks: &[u8]) -> Option<u8> {
-    for i in 2..=10 {
-        let t: Vec<u8> = (i..=i + 4).rev().collect();
-        if ranks == &t[..] {
-            return Some(i + 4);
-        }
+    if ranks.windows(2).all(|pair| pair[0] == pair[1] + 1) {
+        return Some(ranks[0]);
     }
     if ranks == [14, 5, 4, 3, 2] {
-        return Some(1);
+        return Some(5);
     }
     None
 }
 
-fn is_flush(suits: &Vec<Suit>) -> bool {
+fn is_flush(suits: &[Suit]) -> bool {
     suits.iter().all_equal()
 }
 
-fn is_straight_flush(ranks: &[u8], suits: &Vec<Suit>) -> Option<u8> {
-    if is_flush(suits) {
-        is_straight(ranks)
-    } else {
-        None
+/// Groups equal ranks, given sorted from the highest, as `(count, rank)` pairs ordered by how
+/// significant they are: larger groups first, then higher ranks.
+fn groups(ranks: &[u8]) -> Vec<(u8, u8)> {
+    let mut groups: Vec<(u8, u8)> = Vec::with_capacity(ranks.len());
+    for &rank in ranks {
+        match groups.last_mut() {
+            Some((count, last)) if *last == rank => *count += 1,
+            _ => groups.push((1, rank)),
+        }
     }
+    groups.sort_by_key(|&group| Reverse(group));
+    groups
 }
 
 use std::cmp::{Ordering, Reverse};
+use std::fmt;
+use std::str::FromStr;
 use itertools::Itertools;
 
 impl Poker {
     fn new(cards: &[Card]) -> Poker {
+        use Poker::*;
         let mut ranks: Vec<u8> = cards.iter().map(|c| c.rank).collect();
         ranks.sort_by_key(|&x| Reverse(x));
-        let ranks = ranks;
         let suits: Vec<Suit> = cards.iter().map(|c| c.suit).collect();
+        let grouped = Poker::of_ranks(&ranks);
+        // Only wild cards duplicating a card let the ranks of a flush make a better hand.
+        let drawn = match (is_straight(&ranks), is_flush(&suits)) {
+            (Some(high), true) => StraightFlush(high),
+            (Some(high), false) => Straight(high),
+            (None, true) => Flush(ranks[0], ranks[1], ranks[2], ranks[3], ranks[4]),
+            (None, false) => return grouped,
+        };
+        grouped.max(drawn)
+    }
 
-        let freqs = frequencies(&ranks);
-        let shape = frequencies(&freqs.values().map(|&x| x).collect::<Vec<u8>>());
-
-        let freqs_to_vals: HashMap<u8, Vec<u8>> = freqs.iter()
-            .fold(HashMap::new(), |mut acc, (&k, &v)| {
-                acc.entry(v).or_insert(Vec::new()).push(k);
-                acc
-            });
-
-        let get_val = |k| *freqs_to_vals.get(&k).unwrap().first().unwrap();
-
-        let has_shape = |t1: u8, r1: u8, t2: u8, r2: u8|
-            *shape.get(&t1).unwrap_or(&0) == r1 && *shape.get(&t2).unwrap_or(&0) == r2;
-
-        if let Some(high) = is_straight_flush(&ranks, &suits)  {
-            Poker::StraightFlush(high)
-        } else if has_shape(4, 1, 1, 1) {
-            Poker::FourOfAKind(get_val(4), get_val(1))
-        } else if has_shape(3, 1, 2, 1) {
-            Poker::FullHouse(get_val(3), get_val(2))
-        } else if is_flush(&suits) {
-            Poker::Flush(ranks[0])
-        } else if let Some(high) = is_straight(&ranks) {
-            Poker::Straight(high)
-        } else if has_shape(3, 1, 1, 2) {
-            let mut kickers = freqs_to_vals.get(&1).unwrap().to_owned();
-            kickers.sort_by_key(|&x| Reverse(x));
-            Poker::ThreeOfAKind(get_val(3), kickers[0], kickers[1])
-        } else if has_shape(2, 2, 1, 1) {
-            let mut pvs = freqs_to_vals.get(&2).unwrap().to_owned();
-            pvs.sort_by_key(|&x| Reverse(x));
-            Poker::TwoPairs(pvs[0], pvs[1], get_val(1))
-        } else if has_shape(2, 1, 1, 3) {
-            Poker::Pair(get_val(2))
-        } else {
-            Poker::HighSeven(ranks[0], ranks[1], ranks[2], ranks[3], ranks[4])
+    /// The score of five ranks sorted from the highest, as if they made neither a straight nor a
+    /// flush.
+    fn of_ranks(ranks: &[u8]) -> Poker {
+        use Poker::*;
+        match *groups(ranks).as_slice() {
+            [(5, five)] => FiveOfAKind(five),
+            [(4, four), (1, kicker)] => FourOfAKind(four, kicker),
+            [(3, three), (2, pair)] => FullHouse(three, pair),
+            [(3, three), (1, first), (1, second)] => ThreeOfAKind(three, first, second),
+            [(2, high), (2, low), (1, kicker)] => TwoPairs(high, low, kicker),
+            [(2, pair), (1, first), (1, second), (1, third)] => Pair(pair, first, second, third),
+            _ => HighCard(ranks[0], ranks[1], ranks[2], ranks[3], ranks[4]),
         }
     }
-}
 
-impl<'a> Hand<'a> {
-    fn new(str: &str) -> Hand {
-        let cards: Vec<Card> = str.split_whitespace().map(|card| {
-            Card::new(card)
-        }).collect();
-        let score = Poker::new(&cards);
-        Hand { score, str }
+    /// The best score of the natural cards with `wilds` more cards of any rank. The wild cards
+    /// take the suit of the first natural card, so that they complete a flush whenever one can be
+    /// made, which beats any other hand of the same ranks. They may duplicate a natural card, to
+    /// make five aces or a flush with two aces.
+    fn with_wilds(naturals: &[Card], wilds: usize) -> Poker {
+        let suit = naturals.first().map_or(Suit::Spade, |card| card.suit);
+        let mut best = Poker::HighCard(0, 0, 0, 0, 0);
+        substitute(&mut naturals.to_vec(), &vec![suit; wilds], 2, &mut |cards| {
+            best = best.max(Poker::new(cards));
+        });
+        best
     }
 }
 
-use regex::Regex;
+/// Calls `visit` with `cards` completed by a card of each of `suits` for every multiset of ranks
+/// from `lowest` to aces.
+pub(crate) fn substitute(cards: &mut Vec<Card>, suits: &[Suit], lowest: u8, visit: &mut dyn FnMut(&[Card])) {
+    let (&suit, suits) = match suits.split_first() {
+        Some(split) => split,
+        None => return visit(cards),
+    };
+    for rank in lowest..=14 {
+        cards.push(Card { rank, suit });
+        substitute(cards, suits, rank, visit);
+        cards.pop();
+    }
+}
 
-impl Card {
-    fn new(card: &str) -> Card {
-        use Suit::*;
-        lazy_static! {
-        static ref RE: Regex = Regex::new(r"([\dJQKA]{1,2})([HSCD])").unwrap();
+impl Hand {
+    /// Makes a hand of five distinct cards, where jokers stand for whatever card makes the best
+    /// hand.
+    pub fn new(cards: &[Card]) -> Result<Hand, ParseError> {
+        Hand::with_wild_ranks(cards, &[])
     }
-        let caps = RE.captures(card).unwrap();
-        let rank: &str = caps.get(1).unwrap().into();
-        let suit: &str = caps.get(2).unwrap().into();
-        let rank = match rank {
-            "J" => 11,
-            "Q" => 12,
-            "K" => 13,
-            "A" => 14,
-            _ => rank.parse().unwrap(),
-        };
-        let suit = match suit {
-            "H" => Heart,
-            "D" => Diamond,
-            "C" => Club,
-            "S" => Spade,
-            _ => panic!("unrecognided suit: {}", suit)
+
+    /// Makes a hand where jokers and the cards of `wild_ranks`, like 2 for deuces wild, stand for
+    /// whatever card makes the best hand.
+    pub fn with_wild_ranks(cards: &[Card], wild_ranks: &[u8]) -> Result<Hand, ParseError> {
+        if cards.len() != 5 {
+            return Err(ParseError::WrongCardCount(cards.len()));
+        }
+        check_distinct(cards)?;
+        let (wilds, naturals): (Vec<Card>, Vec<Card>) = cards.iter()
+            .partition(|card| card.is_joker() || wild_ranks.contains(&card.rank));
+        let score = if wilds.is_empty() {
+            Poker::new(cards)
+        } else {
+            Poker::with_wilds(&naturals, wilds.len())
         };
-        Card { rank, suit }
+        Ok(Hand { cards: cards.to_vec(), score })
+    }
+
+    pub fn cards(&self) -> &[Card] {
+        &self.cards
+    }
+
+    pub fn score(&self) -> Poker {
+        self.score
+    }
+}
+
+/// Writes the cards as the parser reads them, then the description of the hand, like `KH KD KC 7S
+/// 7H: Full house, Kings over Sevens`. With `{:#}`, suits are written as symbols.
+impl fmt::Display for Hand {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (i, card) in self.cards.iter().enumerate() {
+            let separator = if i == 0 { "" } else { " " };
+            if f.alternate() {
+                write!(f, "{}{:#}", separator, card)?;
+            } else {
+                write!(f, "{}{}", separator, card)?;
+            }
+        }
+        write!(f, ": {}", self.score)
+    }
+}
+
+/// Fails on the first card other than a joker given twice.
+pub(crate) fn check_distinct(cards: &[Card]) -> Result<(), ParseError> {
+    match cards.iter().enumerate().find(|&(i, card)| !card.is_joker() && cards[..i].contains(card)) {
+        Some((_, &card)) => Err(ParseError::DuplicateCard(card)),
+        None => Ok(()),
+    }
+}
+
+/// Parses five cards separated by whitespace, like `4S 5S 7H 8D JC`.
+impl FromStr for Hand {
+    type Err = ParseError;
+
+    fn from_str(hand: &str) -> Result<Self, Self::Err> {
+        Hand::new(&parse_cards(hand)?)
     }
 }
 
-pub fn winning_hands<'a>(hands: &[&'a str]) -> Option<Vec<&'a str>> {
-    let mut hands: Vec<_> = hands.iter().map(|c| Hand::new(c)).collect();
-    hands.sort_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
-    let f = hands.last().unwrap();
-    Some(hands.iter().filter(|&c| c == f).map(|c| c.str).collect())
+fn parse_cards(cards: &str) -> Result<Vec<Card>, ParseError> {
+    cards.split_whitespace().map(str::parse).collect()
+}
+
+/// Returns the best hands, in the order they are given, or the first error met parsing them.
+pub fn winning_hands<'a>(hands: &[&'a str]) -> Result<Vec<&'a str>, ParseError> {
+    winning_hands_with_wild_ranks(hands, &[])
+}
+
+/// Returns the best hands as `winning_hands` does, the cards of `wild_ranks` being wild like
+/// jokers.
+pub fn winning_hands_with_wild_ranks<'a>(hands: &[&'a str], wild_ranks: &[u8]) -> Result<Vec<&'a str>, ParseError> {
+    let parsed = hands.iter()
+        .map(|hand| Hand::with_wild_ranks(&parse_cards(hand)?, wild_ranks))
+        .collect::<Result<Vec<Hand>, _>>()?;
+    let best = match parsed.iter().max() {
+        Some(best) => best,
+        None => return Ok(Vec::new()),
+    };
+    Ok(hands.iter().zip(parsed.iter()).filter(|&(_, hand)| hand == best).map(|(&str, _)| str).collect())
 }
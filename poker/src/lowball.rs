@@ -0,0 +1,146 @@
+//! Games where the lowest hand wins the pot, or splits it with the highest.
+//!
+//! Low hands are scored as `Poker` values compared the other way round, the lowest score being the
+//! best hand. Jokers stand for whatever card makes the lowest hand.
+
+use crate::card::SUITS;
+use itertools::Itertools;
+use std::cmp::Reverse;
+use crate::{substitute, winning_hands, Card, Hand, ParseError, Poker};
+
+/// The rules ranking low hands.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Lowball {
+    /// Aces are low and straights and flushes do not count, so the best hand is `5 4 3 2 A`.
+    /// Aces score as 1.
+    AceToFive,
+    /// Aces are high and straights and flushes count against the hand, so the best hand is
+    /// `7 5 4 3 2` of several suits. `A 2 3 4 5` is no straight but ace high.
+    DeuceToSeven,
+}
+
+impl Lowball {
+    /// The score of a hand under these rules, lower scores being better hands.
+    pub fn score(self, hand: &Hand) -> Poker {
+        let naturals: Vec<Card> = hand.cards().iter().filter(|card| !card.is_joker()).copied().collect();
+        let jokers = hand.cards().len() - naturals.len();
+        if jokers == 0 {
+            return self.score_naturals(&naturals);
+        }
+        // Jokers never make a flush: a single one takes another suit than the first natural card,
+        // and several take different suits.
+        let first = naturals.first()
+            .map_or(0, |card| SUITS.iter().position(|&suit| suit == card.suit).unwrap());
+        let suits: Vec<_> = (0..jokers).map(|i| SUITS[(first + 1 + i) % SUITS.len()]).collect();
+        let mut best = None;
+        substitute(&mut naturals.clone(), &suits, 2, &mut |cards| {
+            let score = self.score_naturals(cards);
+            best = Some(best.map_or(score, |best: Poker| best.min(score)));
+        });
+        best.expect("jokers stand for some card")
+    }
+
+    fn score_naturals(self, cards: &[Card]) -> Poker {
+        match self {
+            Lowball::AceToFive => {
+                let mut ranks: Vec<u8> = cards.iter()
+                    .map(|card| if card.rank == 14 { 1 } else { card.rank })
+                    .collect();
+                ranks.sort_by_key(|&rank| Reverse(rank));
+                Poker::of_ranks(&ranks)
+            }
+            Lowball::DeuceToSeven => match Poker::new(cards) {
+                Poker::Straight(5) => Poker::HighCard(14, 5, 4, 3, 2),
+                Poker::StraightFlush(5) => Poker::Flush(14, 5, 4, 3, 2),
+                score => score,
+            },
+        }
+    }
+
+    /// Returns the lowest hands, in the order they are given, or the first error met parsing them.
+    pub fn winning_hands<'a>(self, hands: &[&'a str]) -> Result<Vec<&'a str>, ParseError> {
+        let scores = hands.iter()
+            .map(|hand| Ok(self.score(&hand.parse()?)))
+            .collect::<Result<Vec<Poker>, _>>()?;
+        Ok(lowest(hands, &scores))
+    }
+}
+
+/// The ace-to-five score of a hand if it qualifies for the low half of a hi-lo pot, with five
+/// different ranks of 8 or lower.
+pub fn eight_or_better(hand: &Hand) -> Option<Poker> {
+    match Lowball::AceToFive.score(hand) {
+        score @ Poker::HighCard(high, ..) if high <= 8 => Some(score),
+        _ => None,
+    }
+}
+
+/// The winners of a pot split between the highest hand and the lowest one: hands, or the indexes
+/// of players.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct HighLow<T> {
+    pub high: Vec<T>,
+    /// Empty when no hand qualifies for the low half, in which case the high hands take the whole
+    /// pot.
+    pub low: Vec<T>,
+}
+
+/// Returns the highest hands and the lowest eight-or-better hands among hands of five cards, or the
+/// first error met parsing them. A hand may win both halves. `omaha` and `stud` choose the five
+/// cards of each half from the cards of each player.
+pub fn winning_hands_high_low<'a>(hands: &[&'a str]) -> Result<HighLow<&'a str>, ParseError> {
+    let high = winning_hands(hands)?;
+    let parsed = hands.iter()
+        .map(|hand| hand.parse())
+        .collect::<Result<Vec<Hand>, _>>()?;
+    let (qualified, scores): (Vec<&str>, Vec<Poker>) = hands.iter()
+        .zip(parsed.iter())
+        .filter_map(|(&str, hand)| Some((str, eight_or_better(hand)?)))
+        .unzip();
+    Ok(HighLow { high, low: lowest(&qualified, &scores) })
+}
+
+/// The best high hand and the best eight-or-better low hand a player makes, from cards chosen
+/// independently.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct SplitHands {
+    pub high: Hand,
+    /// None when no choice of cards qualifies for low.
+    pub low: Option<Hand>,
+}
+
+impl SplitHands {
+    /// The best of each half among `hands`, of which there must be one at least.
+    pub(crate) fn best(hands: Vec<Hand>) -> SplitHands {
+        let low = hands.iter()
+            .filter_map(|hand| Some((eight_or_better(hand)?, hand)))
+            .min_by_key(|&(score, _)| score)
+            .map(|(_, hand)| hand.clone());
+        let high = hands.into_iter().max().expect("there is a hand of five cards at least");
+        SplitHands { high, low }
+    }
+}
+
+/// The indexes of the players winning each half of the pot, given the hands they make.
+pub(crate) fn split_pot(players: &[SplitHands]) -> HighLow<usize> {
+    let high = match players.iter().map(|hands| &hands.high).max() {
+        Some(best) => players.iter().positions(|hands| hands.high == *best).collect(),
+        None => Vec::new(),
+    };
+    let (qualified, scores): (Vec<usize>, Vec<Poker>) = players.iter()
+        .enumerate()
+        .filter_map(|(i, hands)| Some((i, eight_or_better(hands.low.as_ref()?)?)))
+        .unzip();
+    HighLow { high, low: lowest(&qualified, &scores) }
+}
+
+fn lowest<T: Copy>(hands: &[T], scores: &[Poker]) -> Vec<T> {
+    match scores.iter().min() {
+        Some(best) => hands.iter()
+            .zip(scores.iter())
+            .filter(|&(_, score)| score == best)
+            .map(|(&hand, _)| hand)
+            .collect(),
+        None => Vec::new(),
+    }
+}
@@ -0,0 +1,35 @@
+//! Omaha hi-lo showdowns, where each player makes a hand with exactly two of their four hole cards
+//! and three of the community cards, for the high and the low half of the pot separately.
+
+use crate::lowball::{split_pot, HighLow, SplitHands};
+use crate::{check_distinct, Card, Hand, ParseError};
+use itertools::Itertools;
+
+/// Returns the best high hand and the best eight-or-better low hand out of four hole cards and
+/// three to five community cards, each using two hole cards and three community cards. A board of
+/// another size is reported by its own number of cards.
+pub fn best_hands(hole: [Card; 4], board: &[Card]) -> Result<SplitHands, ParseError> {
+    if !(3..=5).contains(&board.len()) {
+        return Err(ParseError::WrongCardCount(board.len()));
+    }
+    let cards: Vec<Card> = hole.iter().chain(board).copied().collect();
+    check_distinct(&cards)?;
+    let hands = hole.iter()
+        .copied()
+        .combinations(2)
+        .cartesian_product(board.iter().copied().combinations(3).collect::<Vec<_>>())
+        .map(|(hole, board)| Hand::new(&[hole, board].concat()))
+        .collect::<Result<Vec<Hand>, _>>()?;
+    Ok(SplitHands::best(hands))
+}
+
+/// Returns the indexes of the players winning the high half of the pot and those winning the low
+/// half, which is empty when no player makes an eight-or-better low.
+pub fn showdown(players: &[[Card; 4]], board: &[Card]) -> Result<HighLow<usize>, ParseError> {
+    let all: Vec<Card> = players.iter().flatten().chain(board).copied().collect();
+    check_distinct(&all)?;
+    let hands = players.iter()
+        .map(|&hole| best_hands(hole, board))
+        .collect::<Result<Vec<SplitHands>, _>>()?;
+    Ok(split_pot(&hands))
+}
@@ -0,0 +1,31 @@
+//! Seven-card stud hi-lo showdowns, where each player makes the best high hand and the best low
+//! hand out of their own cards, choosing the five cards of each independently.
+
+use crate::lowball::{split_pot, HighLow, SplitHands};
+use crate::{check_distinct, Card, Hand, ParseError};
+use itertools::Itertools;
+
+/// Returns the best high hand and the best eight-or-better low hand out of five to seven cards.
+pub fn best_hands(cards: &[Card]) -> Result<SplitHands, ParseError> {
+    if !(5..=7).contains(&cards.len()) {
+        return Err(ParseError::WrongCardCount(cards.len()));
+    }
+    check_distinct(cards)?;
+    let hands = cards.iter()
+        .copied()
+        .combinations(5)
+        .map(|cards| Hand::new(&cards))
+        .collect::<Result<Vec<Hand>, _>>()?;
+    Ok(SplitHands::best(hands))
+}
+
+/// Returns the indexes of the players winning the high half of the pot and those winning the low
+/// half, which is empty when no player makes an eight-or-better low.
+pub fn showdown(players: &[Vec<Card>]) -> Result<HighLow<usize>, ParseError> {
+    let all: Vec<Card> = players.iter().flatten().copied().collect();
+    check_distinct(&all)?;
+    let hands = players.iter()
+        .map(|cards| best_hands(cards))
+        .collect::<Result<Vec<SplitHands>, _>>()?;
+    Ok(split_pot(&hands))
+}
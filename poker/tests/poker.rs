@@ -1,5 +1,14 @@
-use poker::winning_hands;
+// The helpers keep their original, explicit lifetimes.
+#![allow(clippy::needless_lifetimes)]
+
+use poker::equity::{self, Equity};
+use poker::eval::{self, CardCode};
+use poker::lowball::{self, HighLow, Lowball};
+use poker::{holdem, omaha, stud};
+use poker::{winning_hands, winning_hands_with_wild_ranks, Card, Deck, Hand, ParseError, Poker, Suit};
+use std::collections::BTreeMap;
 use std::collections::HashSet;
+use std::convert::TryFrom;
 
 fn hs_from<'a>(input: &[&'a str]) -> HashSet<&'a str> {
     let mut hs = HashSet::new();
@@ -191,3 +200,696 @@ fn test_straight_flush_ranks() {
     // both hands have straight flush, tie goes to highest-ranked card
     test(&["4H 6H 7H 8H 5H", "5S 7S 8S 9S 6S"], &["5S 7S 8S 9S 6S"])
 }
+
+#[test]
+fn test_parse_card() {
+    let card: Card = "10H".parse().unwrap();
+    assert_eq!((card.rank(), card.suit()), (10, Suit::Heart));
+    let card: Card = "QS".parse().unwrap();
+    assert_eq!((card.rank(), card.suit()), (12, Suit::Spade));
+}
+
+#[test]
+fn test_parse_invalid_cards() {
+    for card in &["1H", "11H", "010H", "+5H", "ZD", "H", "10"] {
+        let error = card.parse::<Card>().unwrap_err();
+        match error {
+            ParseError::InvalidRank(_) | ParseError::InvalidSuit(_) => {}
+            _ => panic!("unexpected error {:?} for {:?}", error, card),
+        }
+    }
+    assert_eq!("10X".parse::<Card>(), Err(ParseError::InvalidSuit("10X".to_string())));
+    assert_eq!("1H".parse::<Card>(), Err(ParseError::InvalidRank("1H".to_string())));
+}
+
+#[test]
+fn test_parse_hand() {
+    let hand: Hand = "4S 5S 7H 8D JC".parse().unwrap();
+    assert_eq!(hand.cards().len(), 5);
+    assert_eq!("4S 5S 7H 8D".parse::<Hand>().unwrap_err(), ParseError::WrongCardCount(4));
+    assert_eq!("4S 5S 7H 8D JC QC".parse::<Hand>().unwrap_err(), ParseError::WrongCardCount(6));
+    assert_eq!(
+        "4S 5S 7H 4S JC".parse::<Hand>().unwrap_err(),
+        ParseError::DuplicateCard("4S".parse().unwrap())
+    );
+}
+
+#[test]
+fn test_invalid_hand_is_an_error() {
+    assert_eq!(
+        winning_hands(&["4S 5S 7H 8D JC", "4S 5S 7H 8D XC"]),
+        Err(ParseError::InvalidRank("XC".to_string()))
+    );
+}
+
+#[test]
+fn test_no_hands_have_no_winners() {
+    assert_eq!(winning_hands(&[]), Ok(vec![]));
+}
+
+#[test]
+fn test_pair_kickers_break_ties() {
+    test(&["4S 4H 9S 7D 2C", "4D 4C 9H 7C 3S"], &["4D 4C 9H 7C 3S"])
+}
+
+#[test]
+fn test_flush_kickers_break_ties_down_to_last_card() {
+    test(&["2H 7H 8H 9H JH", "3S 7S 8S 9S JS"], &["3S 7S 8S 9S JS"])
+}
+
+#[test]
+fn test_identical_ranks_in_other_suits_tie() {
+    test(
+        &["2H 7H 8H 9H JH", "2S 7S 8S 9S JS", "2H 7S 8S 9S JS"],
+        &["2H 7H 8H 9H JH", "2S 7S 8S 9S JS"],
+    )
+}
+
+#[test]
+fn test_scores_carry_every_kicker() {
+    let score = |hand: &str| hand.parse::<Hand>().unwrap().score();
+    assert_eq!(score("4S 4H 9S 7D 2C"), Poker::Pair(4, 9, 7, 2));
+    assert_eq!(score("2H 7H 8H 9H JH"), Poker::Flush(11, 9, 8, 7, 2));
+    assert_eq!(score("KS KH KD 7C 7D"), Poker::FullHouse(13, 7));
+    assert_eq!(score("4S AH 3S 2D 5H"), Poker::Straight(5));
+}
+
+/// The ranks of a hand ordered as poker compares them, for a category from 0 for a high card to 8
+/// for a straight flush, computed independently of the crate.
+fn brute_force_rank(cards: &[Card]) -> (u8, Vec<u8>) {
+    let mut counts = [0u8; 15];
+    for card in cards {
+        counts[card.rank() as usize] += 1;
+    }
+    let mut groups: Vec<(u8, u8)> = (2..=14).filter(|&r| counts[r] > 0).map(|r| (counts[r], r as u8)).collect();
+    groups.sort_by(|a, b| b.cmp(a));
+    let shape: Vec<u8> = groups.iter().map(|&(count, _)| count).collect();
+    let ranks: Vec<u8> = groups.iter().map(|&(_, rank)| rank).collect();
+    let flush = cards.iter().all(|card| card.suit() == cards[0].suit());
+    let straight_high = if shape.len() == 5 && ranks[0] - ranks[4] == 4 {
+        Some(ranks[0])
+    } else if ranks == [14, 5, 4, 3, 2] {
+        Some(5)
+    } else {
+        None
+    };
+    match (straight_high, flush, shape.as_slice()) {
+        (Some(high), true, _) => (8, vec![high]),
+        (_, _, [4, 1]) => (7, ranks),
+        (_, _, [3, 2]) => (6, ranks),
+        (_, true, _) => (5, ranks),
+        (Some(high), _, _) => (4, vec![high]),
+        (_, _, [3, 1, 1]) => (3, ranks),
+        (_, _, [2, 2, 1]) => (2, ranks),
+        (_, _, [2, 1, 1, 1]) => (1, ranks),
+        _ => (0, ranks),
+    }
+}
+
+#[test]
+fn test_all_hands_rank_like_brute_force() {
+    let deck: Vec<Card> = ["H", "D", "C", "S"]
+        .iter()
+        .flat_map(|suit| {
+            ["2", "3", "4", "5", "6", "7", "8", "9", "10", "J", "Q", "K", "A"]
+                .iter()
+                .map(move |rank| format!("{}{}", rank, suit).parse().unwrap())
+        })
+        .collect();
+    let codes: Vec<CardCode> = deck.iter().map(|&card| CardCode::try_from(card).unwrap()).collect();
+
+    // Every class of hands the brute force ranker tells apart, with the score and the rank of one of
+    // them.
+    let mut classes: BTreeMap<(u8, Vec<u8>), (Poker, u16)> = BTreeMap::new();
+    let mut category_counts = [0u32; 9];
+    let mut hands = 0;
+    for a in 0..52 {
+        for b in a + 1..52 {
+            for c in b + 1..52 {
+                for d in c + 1..52 {
+                    for e in d + 1..52 {
+                        let cards = [deck[a], deck[b], deck[c], deck[d], deck[e]];
+                        let score = Hand::new(&cards).unwrap().score();
+                        let code_rank = eval::rank([a, b, c, d, e].map(|i| codes[i]));
+                        let rank = brute_force_rank(&cards);
+                        category_counts[rank.0 as usize] += 1;
+                        hands += 1;
+                        let class = *classes.entry(rank).or_insert((score, code_rank));
+                        assert_eq!((score, code_rank), class, "{:?}", cards);
+                    }
+                }
+            }
+        }
+    }
+
+    assert_eq!(hands, 2_598_960);
+    assert_eq!(category_counts, [1_302_540, 1_098_240, 123_552, 54_912, 10_200, 5_108, 3_744, 624, 40]);
+    assert_eq!(classes.len(), 7462);
+    let scores: Vec<Poker> = classes.values().map(|&(score, _)| score).collect();
+    assert!(scores.windows(2).all(|pair| pair[0] < pair[1]));
+    let code_ranks: Vec<u16> = classes.values().map(|&(_, rank)| rank).collect();
+    assert_eq!(code_ranks, (1..=eval::RANKS).collect::<Vec<u16>>());
+    assert!(classes.values().all(|&(score, rank)| eval::score(rank) == Some(score)));
+}
+
+#[test]
+fn test_scores_only_of_ranks() {
+    assert_eq!(eval::score(0), None);
+    assert_eq!(eval::score(1), Some(Poker::HighCard(7, 5, 4, 3, 2)));
+    assert_eq!(eval::score(eval::RANKS), Some(Poker::StraightFlush(14)));
+    assert_eq!(eval::score(eval::RANKS + 1), None);
+}
+
+#[test]
+#[cfg(debug_assertions)]
+#[should_panic(expected = "cards must be distinct")]
+fn test_rank_rejects_repeated_cards() {
+    let ace = CardCode::new(51).unwrap();
+    eval::rank([ace; 5]);
+}
+
+fn cards(cards: &str) -> Vec<Card> {
+    cards.split_whitespace().map(|card| card.parse().unwrap()).collect()
+}
+
+fn hole(hole: &str) -> [Card; 2] {
+    let cards = cards(hole);
+    [cards[0], cards[1]]
+}
+
+#[test]
+fn test_best_hand_uses_hole_and_community_cards() {
+    let hand = holdem::best_hand(hole("AH KH"), &cards("QH JH 2C 10H 3D")).unwrap();
+    assert_eq!(hand.score(), Poker::StraightFlush(14));
+    let mut best = hand.cards().to_vec();
+    best.sort_by_key(|card| card.rank());
+    assert_eq!(best, cards("10H JH QH KH AH"));
+}
+
+#[test]
+fn test_best_hand_can_play_the_board() {
+    let hand = holdem::best_hand(hole("2C 3D"), &cards("AS KS QS JS 10S")).unwrap();
+    assert_eq!(hand.score(), Poker::StraightFlush(14));
+}
+
+#[test]
+fn test_best_hand_picks_best_kickers() {
+    let hand = holdem::best_hand(hole("AH 9C"), &cards("AD 8S 7S 4C 2D")).unwrap();
+    assert_eq!(hand.score(), Poker::Pair(14, 9, 8, 7));
+}
+
+#[test]
+fn test_best_hand_on_the_flop_and_turn() {
+    assert_eq!(holdem::best_hand(hole("7H 7D"), &cards("7S KD KC")).unwrap().score(), Poker::FullHouse(7, 13));
+    assert_eq!(
+        holdem::best_hand(hole("7H 8D"), &cards("9S 10D 2C JC")).unwrap().score(),
+        Poker::Straight(11)
+    );
+}
+
+#[test]
+fn test_best_hand_with_wrong_or_duplicate_cards() {
+    assert_eq!(holdem::best_hand(hole("7H 7D"), &cards("7S KD")).unwrap_err(), ParseError::WrongCardCount(4));
+    assert_eq!(
+        holdem::best_hand(hole("7H 7D"), &cards("7S KD KC 2C 3C 4C")).unwrap_err(),
+        ParseError::WrongCardCount(8)
+    );
+    assert_eq!(
+        holdem::best_hand(hole("7H 7D"), &cards("7S KD 7H")).unwrap_err(),
+        ParseError::DuplicateCard("7H".parse().unwrap())
+    );
+}
+
+#[test]
+fn test_showdown_winners() {
+    let board = cards("2H 7C 9D JS KH");
+    assert_eq!(holdem::showdown(&[hole("AS 3C"), hole("9H 4C"), hole("QD 8C")], &board), Ok(vec![1]));
+    let board = cards("5H 6C 7D 8S 9H");
+    assert_eq!(holdem::showdown(&[hole("AS 2C"), hole("KH 3C"), hole("10D 2D")], &board), Ok(vec![2]));
+    // Both play the straight on the board.
+    assert_eq!(holdem::showdown(&[hole("AS 2C"), hole("KH 3C")], &board), Ok(vec![0, 1]));
+    assert_eq!(
+        holdem::showdown(&[hole("AS 2C"), hole("AS 3C")], &board),
+        Err(ParseError::DuplicateCard("AS".parse().unwrap()))
+    );
+}
+
+#[test]
+fn test_card_codes() {
+    for code in 0..52 {
+        let code = CardCode::new(code).unwrap();
+        assert_eq!(CardCode::try_from(Card::from(code)), Ok(code));
+    }
+    assert_eq!(CardCode::new(52), None);
+    assert_eq!(CardCode::try_from("2H".parse::<Card>().unwrap()).unwrap().code(), 0);
+    assert_eq!(CardCode::try_from("AS".parse::<Card>().unwrap()).unwrap().code(), 51);
+    assert_eq!(CardCode::try_from(Card::JOKER), Err(ParseError::UnexpectedJoker));
+}
+
+#[test]
+fn test_best_rank_agrees_with_best_hand() {
+    let deals = [
+        ("AH KH", "QH JH 2C 10H 3D"),
+        ("2C 3D", "AS KS QS JS 10S"),
+        ("AH 9C", "AD 8S 7S 4C 2D"),
+        ("7H 7D", "7S KD KC"),
+        ("7H 8D", "9S 10D 2C JC"),
+        ("5C 5D", "5H 5S AC KD QD"),
+    ];
+    for &(hole_cards, board) in deals.iter() {
+        let all: Vec<CardCode> = cards(hole_cards).into_iter()
+            .chain(cards(board))
+            .map(|card| CardCode::try_from(card).unwrap())
+            .collect();
+        let best = holdem::best_hand(hole(hole_cards), &cards(board)).unwrap();
+        assert_eq!(eval::score(eval::best_rank(&all)), Some(best.score()), "{} {}", hole_cards, board);
+    }
+}
+
+#[test]
+fn test_equity_on_the_flop_agrees_with_showdowns() {
+    let players = [hole("AH AS"), hole("KD KC"), hole("9C 8C")];
+    let flop = cards("KH 7C 2C");
+    let equities = equity::exhaustive(&players, &flop).unwrap();
+    let mut expected = vec![Equity::default(); players.len()];
+    let deck: Vec<Card> = (0..52)
+        .map(|code| Card::from(CardCode::new(code).unwrap()))
+        .filter(|card| !flop.contains(card) && !players.iter().any(|hole| hole.contains(card)))
+        .collect();
+    for (i, &turn) in deck.iter().enumerate() {
+        for &river in &deck[i + 1..] {
+            let board: Vec<Card> = flop.iter().copied().chain([turn, river]).collect();
+            let winners = holdem::showdown(&players, &board).unwrap();
+            for (player, expected) in expected.iter_mut().enumerate() {
+                if !winners.contains(&player) {
+                    expected.losses += 1;
+                } else if winners.len() == 1 {
+                    expected.wins += 1;
+                    expected.pots += 1.0;
+                } else {
+                    expected.ties += 1;
+                    expected.pots += 1.0 / winners.len() as f64;
+                }
+            }
+        }
+    }
+    assert_eq!(equities, expected);
+    assert_eq!(equities[0].boards(), 43 * 42 / 2);
+}
+
+#[test]
+fn test_equity_of_a_complete_board() {
+    let board = cards("5H 6C 7D 8S 9H");
+    let equities = equity::exhaustive(&[hole("AS 2C"), hole("KH 3C"), hole("10D 2D")], &board).unwrap();
+    let expected = [(0, 0, 1), (0, 0, 1), (1, 0, 0)];
+    for (equity, &(wins, ties, losses)) in equities.iter().zip(expected.iter()) {
+        assert_eq!((equity.wins, equity.ties, equity.losses), (wins, ties, losses));
+    }
+    // Both play the straight on the board.
+    let equities = equity::exhaustive(&[hole("AS 2C"), hole("KH 3C")], &board).unwrap();
+    assert!(equities.iter().all(|equity| equity.ties == 1 && equity.pots == 0.5));
+    assert_eq!(equities[0].equity(), 0.5);
+}
+
+#[test]
+fn test_monte_carlo_equity_is_reproducible_and_close() {
+    let players = [hole("AH AS"), hole("KD KC")];
+    let first = equity::monte_carlo(&players, &[], 20_000, 7).unwrap();
+    assert_eq!(equity::monte_carlo(&players, &[], 20_000, 7).unwrap(), first);
+    assert_ne!(equity::monte_carlo(&players, &[], 20_000, 8).unwrap(), first);
+    assert_eq!(first[0].boards(), 20_000);
+    // Aces hold against kings about 82% of the time.
+    assert!((first[0].equity() - 0.82).abs() < 0.02, "{:?}", first);
+    assert!((first[0].equity() + first[1].equity() - 1.0).abs() < 1e-9);
+
+    // Sampling the boards of the turn comes close to dealing all of them.
+    let turn = cards("KH 7C 2C QD");
+    let exact = equity::exhaustive(&players, &turn).unwrap();
+    let sampled = equity::monte_carlo(&players, &turn, 20_000, 7).unwrap();
+    assert!((exact[0].equity() - sampled[0].equity()).abs() < 0.02);
+}
+
+#[test]
+fn test_calculate_samples_only_when_there_are_too_many_boards() {
+    let players = [hole("AH AS"), hole("KD KC")];
+    let flop = cards("KH 7C 2C");
+    assert_eq!(equity::calculate(&players, &flop, 1000, 1), equity::exhaustive(&players, &flop));
+    let preflop = equity::calculate(&players, &[], 1000, 1).unwrap();
+    assert_eq!(preflop[0].boards(), 1000);
+    assert_eq!(Ok(preflop), equity::monte_carlo(&players, &[], 1000, 1));
+}
+
+#[test]
+fn test_equity_with_wrong_or_duplicate_cards() {
+    let players = [hole("AH AS"), hole("KD KC")];
+    assert_eq!(equity::exhaustive(&players, &cards("2C 3C 4C 5C 6C 7C")), Err(ParseError::WrongCardCount(6)));
+    assert_eq!(
+        equity::exhaustive(&[hole("AH AS"), hole("KD AS")], &[]),
+        Err(ParseError::DuplicateCard("AS".parse().unwrap()))
+    );
+    assert_eq!(
+        equity::monte_carlo(&players, &cards("KD 2C 3C"), 10, 0),
+        Err(ParseError::DuplicateCard("KD".parse().unwrap()))
+    );
+}
+
+#[test]
+fn test_equity_with_too_many_players() {
+    for &players in [24, 25, 26].iter() {
+        let holes = Deck::new().deal_hands::<2>(players).unwrap();
+        assert_eq!(equity::calculate(&holes, &[], 10, 0), Err(ParseError::TooManyCards(players * 2)));
+        assert_eq!(equity::monte_carlo(&holes, &[], 10, 0), Err(ParseError::TooManyCards(players * 2)));
+        assert_eq!(equity::exhaustive(&holes, &[]), Err(ParseError::TooManyCards(players * 2)));
+    }
+    let mut deck = Deck::new();
+    let holes = deck.deal_hands::<2>(23).unwrap();
+    let equities = equity::calculate(&holes, &[], 10, 0).unwrap();
+    assert_eq!(equities[0].boards(), 6);
+    let flop = deck.deal_cards(3).unwrap();
+    assert_eq!(equity::exhaustive(&holes, &flop).unwrap()[0].boards(), 3);
+    assert_eq!(ParseError::TooManyCards(48).to_string(), "too many cards dealt to complete the board: 48");
+}
+
+#[test]
+fn test_equity_rejects_jokers() {
+    let aces = hole("AH AS");
+    let kings = hole("KD KH");
+    assert_eq!(equity::calculate(&[[Card::JOKER, aces[1]], kings], &[], 1000, 1), Err(ParseError::UnexpectedJoker));
+    assert_eq!(equity::exhaustive(&[aces, kings], &cards("2C 3C JK")), Err(ParseError::UnexpectedJoker));
+    assert_eq!(equity::monte_carlo(&[hole("JK JK"), kings], &[], 10, 0), Err(ParseError::UnexpectedJoker));
+}
+
+#[test]
+fn test_equity_of_no_boards_is_zero() {
+    let equities = equity::monte_carlo(&[hole("AH AS"), hole("KD KC")], &[], 0, 0).unwrap();
+    assert_eq!(equities[0].boards(), 0);
+    assert_eq!((equities[0].equity(), equities[0].win_rate(), equities[0].tie_rate()), (0.0, 0.0, 0.0));
+    assert_eq!(Equity::default().equity(), 0.0);
+}
+
+#[test]
+fn test_new_deck_has_every_card_once() {
+    let deck = Deck::new();
+    assert_eq!(deck.len(), 52);
+    assert_eq!(deck.cards()[0], "2H".parse().unwrap());
+    assert_eq!(deck.cards()[51], "AS".parse().unwrap());
+    assert_eq!(deck.cards().iter().collect::<HashSet<_>>().len(), 52);
+    assert_eq!(Deck::default(), deck);
+}
+
+#[test]
+fn test_shuffling_is_seeded() {
+    let deck = Deck::shuffled(42);
+    assert_eq!(Deck::shuffled(42), deck);
+    assert_ne!(Deck::shuffled(43), deck);
+    assert_ne!(deck, Deck::new());
+    let mut sorted = deck.cards().to_vec();
+    sorted.sort_by_key(|&card| CardCode::try_from(card).unwrap());
+    assert_eq!(sorted, Deck::new().cards());
+}
+
+#[test]
+fn test_dealing_and_burning() {
+    let mut deck = Deck::shuffled(1);
+    let top = deck.cards().to_vec();
+    assert_eq!(deck.deal(), Some(top[0]));
+    assert_eq!(deck.burn(), Some(top[1]));
+    assert_eq!(deck.deal_cards(3), Some(top[2..5].to_vec()));
+    assert_eq!(deck.len(), 47);
+    assert_eq!(deck.deal_cards(48), None);
+    assert_eq!(deck.len(), 47);
+    assert_eq!(deck.deal_cards(47).map(|cards| cards.len()), Some(47));
+    assert!(deck.is_empty());
+    assert_eq!(deck.deal(), None);
+    assert_eq!(deck.burn(), None);
+}
+
+#[test]
+fn test_dealing_hands_around_the_table() {
+    let mut deck = Deck::new();
+    let holes = deck.deal_hands::<2>(3).unwrap();
+    assert_eq!(holes, vec![hole("2H 2S"), hole("2D 3H"), hole("2C 3D")]);
+    let hands = deck.deal_hands::<5>(9).unwrap();
+    assert_eq!(hands.len(), 9);
+    assert!(hands.iter().all(|hand| Hand::new(hand).is_ok()));
+    assert_eq!(deck.len(), 1);
+    assert_eq!(deck.deal_hands::<2>(1), None);
+    assert_eq!(deck.deal_hands::<2>(usize::MAX), None);
+    assert_eq!(deck.len(), 1);
+}
+
+#[test]
+fn test_removing_dead_cards() {
+    let mut deck = Deck::shuffled(5);
+    let dead: Card = "QS".parse().unwrap();
+    assert!(deck.remove(dead));
+    assert!(!deck.remove(dead));
+    assert_eq!(deck.len(), 51);
+    assert!(!deck.cards().contains(&dead));
+    let top = deck.deal().unwrap();
+    // Cards already dealt are no longer in the deck.
+    assert!(!deck.remove(top));
+}
+
+#[test]
+fn test_combinations_of_cards_left() {
+    let mut deck = Deck::new();
+    for card in cards("AH AS KD KC 2H") {
+        deck.remove(card);
+    }
+    assert_eq!(deck.combinations(2).count(), 47 * 46 / 2);
+    assert_eq!(deck.combinations(0).count(), 1);
+    let mut combinations = deck.combinations(3);
+    assert_eq!(combinations.next(), Some(cards("2D 2C 2S")));
+    assert_eq!(combinations.next(), Some(cards("2D 2C 3H")));
+    assert_eq!(Deck::new().combinations(5).count(), 2_598_960);
+}
+
+fn wild_score(hand: &str, wild_ranks: &[u8]) -> Poker {
+    Hand::with_wild_ranks(&cards(hand), wild_ranks).unwrap().score()
+}
+
+#[test]
+fn test_parse_joker() {
+    let joker: Card = "JK".parse().unwrap();
+    assert_eq!(joker, Card::JOKER);
+    assert!(joker.is_joker());
+    assert_eq!((joker.rank(), joker.suit()), (0, Suit::Joker));
+    assert!(!"JH".parse::<Card>().unwrap().is_joker());
+    // Unlike other cards, jokers can be dealt twice.
+    assert!("JK JK 7H 8D JC".parse::<Hand>().is_ok());
+}
+
+#[test]
+fn test_jokers_make_the_best_hand() {
+    let score = |hand: &str| hand.parse::<Hand>().unwrap().score();
+    assert_eq!(score("JK 4S 9H 8D JC"), Poker::Pair(11, 9, 8, 4));
+    assert_eq!(score("JK 4S 9H 9D JC"), Poker::ThreeOfAKind(9, 11, 4));
+    assert_eq!(score("JK 2C 3D 4H 5S"), Poker::Straight(6));
+    assert_eq!(score("JK 3H 7H 9H KH"), Poker::Flush(14, 13, 9, 7, 3));
+    assert_eq!(score("JK 5H 6H 7H 8H"), Poker::StraightFlush(9));
+    assert_eq!(score("JK JK 10S QS KS"), Poker::StraightFlush(14));
+    assert_eq!(score("JK 7H 7D 7C 7S"), Poker::FiveOfAKind(7));
+    assert_eq!(score("JK JK JK JK JK"), Poker::FiveOfAKind(14));
+}
+
+#[test]
+fn test_wild_ranks() {
+    assert_eq!(wild_score("2C 2D 5S 5H KD", &[2]), Poker::FourOfAKind(5, 13));
+    assert_eq!(wild_score("2C 2D 5S 5H KD", &[]), Poker::TwoPairs(5, 2, 13));
+    assert_eq!(wild_score("2C 3D 5S 6H JK", &[2, 3]), Poker::FourOfAKind(6, 5));
+    assert_eq!(wild_score("2C 2D 2H 2S JK", &[2]), Poker::FiveOfAKind(14));
+    // A wild card may duplicate a natural one.
+    assert_eq!(wild_score("2C AS 9S 7S 4S", &[2]), Poker::Flush(14, 14, 9, 7, 4));
+}
+
+#[test]
+fn test_five_of_a_kind_beats_a_straight_flush() {
+    let hands = ["10S JS QS KS AS", "2C 9H 9D 9C 9S"];
+    assert_eq!(winning_hands_with_wild_ranks(&hands, &[2]), Ok(vec![hands[1]]));
+    assert_eq!(winning_hands(&hands), Ok(vec![hands[0]]));
+    let hands = ["JK 8H 8D 8C 8S", "AH AD AC AS JK"];
+    assert_eq!(winning_hands(&hands), Ok(vec![hands[1]]));
+}
+
+fn low_score(rules: Lowball, hand: &str) -> Poker {
+    rules.score(&hand.parse().unwrap())
+}
+
+#[test]
+fn test_ace_to_five_lowball() {
+    use Lowball::AceToFive;
+    assert_eq!(low_score(AceToFive, "AH 2D 3C 4S 5H"), Poker::HighCard(5, 4, 3, 2, 1));
+    // Flushes do not count.
+    assert_eq!(low_score(AceToFive, "AH 2H 3H 4H 6H"), Poker::HighCard(6, 4, 3, 2, 1));
+    assert_eq!(low_score(AceToFive, "AH AD 3C 4S 5H"), Poker::Pair(1, 5, 4, 3));
+    assert_eq!(low_score(AceToFive, "JK 2D 3C 4S 5H"), Poker::HighCard(5, 4, 3, 2, 1));
+    let hands = ["AH 2D 3C 4S 6H", "2H 3D 4C 5S 6S", "AS 2S 3S 4H 5D", "KH QD JC 9S 8H"];
+    assert_eq!(AceToFive.winning_hands(&hands), Ok(vec![hands[2]]));
+    let hands = ["AH 2D 3C 4S 6H", "AD 2H 3S 4C 6C", "2H 3D 4C 5S 7S"];
+    assert_eq!(AceToFive.winning_hands(&hands), Ok(vec![hands[0], hands[1]]));
+    let hands = ["AH AD 3C 4S 5H", "KH QD JC 9S 8H"];
+    assert_eq!(AceToFive.winning_hands(&hands), Ok(vec![hands[1]]));
+}
+
+#[test]
+fn test_deuce_to_seven_lowball() {
+    use Lowball::DeuceToSeven;
+    assert_eq!(low_score(DeuceToSeven, "7H 5D 4C 3S 2H"), Poker::HighCard(7, 5, 4, 3, 2));
+    assert_eq!(low_score(DeuceToSeven, "AH 2D 3C 4S 5H"), Poker::HighCard(14, 5, 4, 3, 2));
+    assert_eq!(low_score(DeuceToSeven, "6H 5D 4C 3S 2H"), Poker::Straight(6));
+    // A joker avoids the flush and pairs.
+    assert_eq!(low_score(DeuceToSeven, "JK 5H 4H 3H 2H"), Poker::HighCard(7, 5, 4, 3, 2));
+    let hands = ["7H 5D 4C 3S 2H", "6H 5D 4C 3S 2H", "AH 2D 3C 4S 5H", "7S 5S 4S 3S 2S"];
+    assert_eq!(DeuceToSeven.winning_hands(&hands), Ok(vec![hands[0]]));
+    let hands = ["8H 6D 4C 3S 2H", "AH 2D 3C 4S 5H"];
+    assert_eq!(DeuceToSeven.winning_hands(&hands), Ok(vec![hands[0]]));
+    assert_eq!(DeuceToSeven.winning_hands(&[]), Ok(vec![]));
+    assert_eq!(DeuceToSeven.winning_hands(&["2H 2H 3C 4S 5D"]), Err(ParseError::DuplicateCard("2H".parse().unwrap())));
+}
+
+#[test]
+fn test_eight_or_better_qualifier() {
+    let qualifies = |hand: &str| lowball::eight_or_better(&hand.parse().unwrap()).is_some();
+    assert!(qualifies("8H 6D 4C 3S AH"));
+    assert!(qualifies("AH 2H 3H 4H 5H"));
+    assert!(!qualifies("9H 6D 4C 3S AH"));
+    assert!(!qualifies("8H 8D 4C 3S AH"));
+}
+
+#[test]
+fn test_high_low_split() {
+    let hands = ["AH 2D 3C 4S 6H", "KH KD KC 9S 8H", "8H 6D 4C 3S 2H"];
+    let expected = HighLow { high: vec![hands[1]], low: vec![hands[0]] };
+    assert_eq!(lowball::winning_hands_high_low(&hands), Ok(expected));
+    // The wheel is a straight for the high half too.
+    let hands = ["AH 2D 3C 4S 5H", "KH KD KC 9S 8H"];
+    let expected = HighLow { high: vec![hands[0]], low: vec![hands[0]] };
+    assert_eq!(lowball::winning_hands_high_low(&hands), Ok(expected));
+    let hands = ["KH KD KC 9S 8H", "9H 6D 4C 3S 2H"];
+    let expected = HighLow { high: vec![hands[0]], low: vec![] };
+    assert_eq!(lowball::winning_hands_high_low(&hands), Ok(expected));
+    assert_eq!(
+        lowball::winning_hands_high_low(&["KH KD KC 9S 8H", "9H 6D"]),
+        Err(ParseError::WrongCardCount(2))
+    );
+}
+
+#[test]
+fn test_card_formatting_round_trips() {
+    for card in Deck::new().cards().iter().chain([Card::JOKER].iter()) {
+        assert_eq!(card.to_string().parse::<Card>().as_ref(), Ok(card));
+        assert_eq!(format!("{:#}", card).parse::<Card>().as_ref(), Ok(card));
+    }
+    let ten: Card = "TH".parse().unwrap();
+    assert_eq!(ten, "10H".parse().unwrap());
+    assert_eq!(ten, "10♥".parse().unwrap());
+    assert_eq!(ten, "T♡".parse().unwrap());
+    assert_eq!(ten.to_string(), "10H");
+    assert_eq!(format!("{:#}", ten), "10♥");
+    assert_eq!(format!("{:#}", "QS".parse::<Card>().unwrap()), "Q♠");
+    assert_eq!(Card::JOKER.to_string(), "JK");
+    assert_eq!(format!("{} {:#}", Suit::Club, Suit::Diamond), "C ♦");
+    assert_eq!("T♥".parse::<Hand>().unwrap_err(), ParseError::WrongCardCount(1));
+    assert_eq!("1♥".parse::<Card>(), Err(ParseError::InvalidRank("1♥".to_string())));
+}
+
+#[test]
+fn test_error_messages() {
+    let error = "4S 5S 7H 4S JC".parse::<Hand>().unwrap_err();
+    assert_eq!(error.to_string(), "duplicate card 4S");
+    assert_eq!(ParseError::WrongCardCount(4).to_string(), "wrong number of cards: 4");
+}
+
+#[test]
+fn test_hand_descriptions() {
+    let describe = |hand: &str| hand.parse::<Hand>().unwrap().score().to_string();
+    assert_eq!(describe("KH KD KC 7S 7H"), "Full house, Kings over Sevens");
+    assert_eq!(describe("AH 2D 3C 4S 5H"), "Straight, Five high (wheel)");
+    assert_eq!(describe("6H 7D 8C 9S 10H"), "Straight, Ten high");
+    assert_eq!(describe("2H 7D 9C JS KH"), "High card, King");
+    assert_eq!(describe("6H 6D 9C JS KH"), "Pair of Sixes");
+    assert_eq!(describe("6H 6D 9C 9S KH"), "Two pairs, Nines and Sixes");
+    assert_eq!(describe("QH QD QC JS KH"), "Three of a kind, Queens");
+    assert_eq!(describe("2H 7H 9H JH KH"), "Flush, King high");
+    assert_eq!(describe("3H 3D 3C 3S KH"), "Four of a kind, Threes");
+    assert_eq!(describe("AH 2H 3H 4H 5H"), "Straight flush, Five high (wheel)");
+    assert_eq!(describe("9S 10S JS QS KS"), "Straight flush, King high");
+    assert_eq!(describe("10S JS QS KS AS"), "Royal flush");
+    assert_eq!(describe("JK AH AD AC AS"), "Five of a kind, Aces");
+    assert_eq!(low_score(Lowball::AceToFive, "AH 2D 3C 4S 6H").to_string(), "High card, Six");
+    assert_eq!(low_score(Lowball::AceToFive, "AH AD 3C 4S 6H").to_string(), "Pair of Aces");
+    assert_eq!(Poker::HighCard(0, 0, 0, 0, 0).to_string(), "High card, 0");
+    assert_eq!(Poker::FullHouse(15, 2).to_string(), "Full house, 15s over Twos");
+
+    let hand: Hand = "KH KD KC 7S TH".parse().unwrap();
+    assert_eq!(hand.to_string(), "KH KD KC 7S 10H: Three of a kind, Kings");
+    assert_eq!(format!("{:#}", hand), "K♥ K♦ K♣ 7♠ 10♥: Three of a kind, Kings");
+}
+
+fn omaha_hole(hole: &str) -> [Card; 4] {
+    let cards = cards(hole);
+    [cards[0], cards[1], cards[2], cards[3]]
+}
+
+#[test]
+fn test_omaha_uses_two_hole_cards_and_three_from_the_board() {
+    let board = cards("2H 5H 8H KH QC");
+    // A single heart in the hole makes no flush, however many are on the board.
+    let hands = omaha::best_hands(omaha_hole("AH 3D 9S 9C"), &board).unwrap();
+    assert_eq!(hands.high.score(), Poker::Pair(9, 13, 12, 8));
+    assert_eq!(lowball::eight_or_better(hands.low.as_ref().unwrap()), Some(Poker::HighCard(8, 5, 3, 2, 1)));
+    let hands = omaha::best_hands(omaha_hole("JH 10H 4C 4D"), &board).unwrap();
+    assert_eq!(hands.high.score(), Poker::Flush(13, 11, 10, 8, 5));
+    assert_eq!(hands.low, None);
+    // The board alone would make a flush, but only three of its cards can be played.
+    let hands = omaha::best_hands(omaha_hole("AS 4D KC QC"), &cards("2H 3H 5H KH 9H")).unwrap();
+    assert_eq!(hands.high.score(), Poker::Straight(5));
+}
+
+#[test]
+fn test_omaha_hi_lo_showdown() {
+    let board = cards("2H 5H 8H KH QC");
+    let players = [omaha_hole("AH 3D 9S 9C"), omaha_hole("AD 3C 6S 6C"), omaha_hole("JH 10H 4C 4D")];
+    assert_eq!(omaha::showdown(&players, &board), Ok(HighLow { high: vec![2], low: vec![0, 1] }));
+    let board = cards("KS QD 9D 10S JC");
+    assert_eq!(omaha::showdown(&players, &board), Ok(HighLow { high: vec![2], low: vec![] }));
+    assert_eq!(omaha::showdown(&[], &board), Ok(HighLow { high: vec![], low: vec![] }));
+}
+
+#[test]
+fn test_omaha_with_wrong_or_duplicate_cards() {
+    assert_eq!(omaha::best_hands(omaha_hole("AH 3D 9S 9C"), &cards("2H 5H")), Err(ParseError::WrongCardCount(2)));
+    assert_eq!(
+        omaha::best_hands(omaha_hole("AH 3D 9S 9C"), &cards("2H 5H 8H KH QC 4C")),
+        Err(ParseError::WrongCardCount(6))
+    );
+    assert_eq!(
+        omaha::showdown(&[omaha_hole("AH 3D 9S 9C"), omaha_hole("AD 3C 6S 9C")], &cards("2H 5H 8H")),
+        Err(ParseError::DuplicateCard("9C".parse().unwrap()))
+    );
+}
+
+#[test]
+fn test_stud_chooses_high_and_low_independently() {
+    let hands = stud::best_hands(&cards("AS 2S 6C 7C 8D KC KH")).unwrap();
+    assert_eq!(hands.high.score(), Poker::Pair(13, 14, 8, 7));
+    assert_eq!(lowball::eight_or_better(hands.low.as_ref().unwrap()), Some(Poker::HighCard(8, 7, 6, 2, 1)));
+    let hands = stud::best_hands(&cards("AH 2D 3C 4S 5H KS KD")).unwrap();
+    assert_eq!(hands.high.score(), Poker::Straight(5));
+    assert_eq!(hands.low.map(|low| low.score()), Some(Poker::Straight(5)));
+    let hands = stud::best_hands(&cards("QH QC QD 9H 9S")).unwrap();
+    assert_eq!(hands.low, None);
+}
+
+#[test]
+fn test_stud_hi_lo_showdown() {
+    let players = [cards("AH 2D 3C 4S 5H KS KD"), cards("AS 2S 6C 7C 8D KC KH"), cards("QH QC QD 9H 9S 4D 10D")];
+    assert_eq!(stud::showdown(&players), Ok(HighLow { high: vec![2], low: vec![0] }));
+    assert_eq!(stud::showdown(&players[1..]), Ok(HighLow { high: vec![1], low: vec![0] }));
+    assert_eq!(stud::best_hands(&cards("AH 2D 3C 4S")).unwrap_err(), ParseError::WrongCardCount(4));
+    assert_eq!(
+        stud::showdown(&[cards("AH 2D 3C 4S 5H"), cards("AH KD QC JS 9H")]),
+        Err(ParseError::DuplicateCard("AH".parse().unwrap()))
+    );
+}
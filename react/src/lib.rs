@@ -1,5 +1,9 @@
 use std::collections::{BTreeSet, HashMap};
-use std::iter::FromIterator;
+
+#[cfg(feature = "stream")]
+use futures::channel::mpsc::{unbounded, UnboundedSender};
+#[cfg(feature = "stream")]
+use futures::stream::Stream;
 
 /// `InputCellID` is a unique identifier for an input cell.
 #[derive(Clone, Copy, Debug, PartialEq)]
@@ -47,6 +51,13 @@ pub enum RemoveCallbackError {
 pub struct Reactor<'a, T> {
     data: Vec<Cell<'a, T>>,
     compute_info: HashMap<usize, ComputeInfo<'a, T>>,
+    history: Option<History<T>>,
+}
+
+impl<'a, T: Copy + PartialEq> Default for Reactor<'a, T> {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 // You are guaranteed that Reactor will only be tested against types that are Copy + PartialEq.
@@ -55,6 +66,16 @@ impl<'a, T: Copy + PartialEq> Reactor<'a, T> {
         Reactor {
             data: Vec::new(),
             compute_info: HashMap::new(),
+            history: None,
+        }
+    }
+
+    // Creates a Reactor that records every change to its input cells as a revision, which can
+    // then be stepped through with `undo` and `redo`.
+    pub fn with_history() -> Self {
+        Reactor {
+            history: Some(History::new()),
+            ..Self::new()
         }
     }
 
@@ -126,14 +147,61 @@ impl<'a, T: Copy + PartialEq> Reactor<'a, T> {
     //
     // As before, that turned out to add too much extra complexity.
     pub fn set_value(&mut self, id: InputCellID, new_value: T) -> bool {
-        let exists = self.data.get_mut(id.0).map(|cell| {
-            cell.value = new_value;
-            true
-        }).unwrap_or(false);
-        if exists {
-            self.propogate(id.0);
+        self.set_values(&[(id, new_value)])
+    }
+
+    // Sets the values of several input cells at once, propagating only after all of them are set.
+    //
+    // Returns false, without changing anything, if any of the cells does not exist.
+    //
+    // Callbacks follow the same rules as for a single set_value call: each compute cell's callbacks
+    // are called at most once, with the final value, and only if that value changed. In history
+    // mode the whole batch is recorded as a single revision.
+    pub fn set_values(&mut self, values: &[(InputCellID, T)]) -> bool {
+        if values.iter().any(|(id, _)| id.0 >= self.data.len()) {
+            return false;
+        }
+        let values: Vec<(usize, T)> = values.iter().map(|&(id, value)| (id.0, value)).collect();
+        let changes = self.apply(&values);
+        if let Some(history) = self.history.as_mut() {
+            history.record(changes);
         }
-        exists
+        true
+    }
+
+    // Reverts the inputs changed by the most recent revision and re-propagates.
+    //
+    // Returns false if there is nothing to undo, or the Reactor was not created with history.
+    pub fn undo(&mut self) -> bool {
+        let changes = match self.history.as_mut().and_then(History::undo) {
+            Some(changes) => changes,
+            None => return false,
+        };
+        let values: Vec<(usize, T)> = changes.iter().map(|change| (change.idx, change.old)).collect();
+        self.apply(&values);
+        true
+    }
+
+    // Re-applies the most recently undone revision and re-propagates.
+    //
+    // Returns false if there is nothing to redo. Any set_value call after an undo discards the
+    // revisions that could have been redone.
+    pub fn redo(&mut self) -> bool {
+        let changes = match self.history.as_mut().and_then(History::redo) {
+            Some(changes) => changes,
+            None => return false,
+        };
+        let values: Vec<(usize, T)> = changes.iter().map(|change| (change.idx, change.new)).collect();
+        self.apply(&values);
+        true
+    }
+
+    // Returns the number of revisions currently applied: 0 for the initial state, incremented by
+    // every set_value call that changes an input and by every redo, decremented by every undo.
+    //
+    // Always 0 if the Reactor was not created with history.
+    pub fn revision(&self) -> usize {
+        self.history.as_ref().map_or(0, |history| history.current)
     }
 
     // Adds a callback to the specified compute cell.
@@ -148,7 +216,7 @@ impl<'a, T: Copy + PartialEq> Reactor<'a, T> {
     // * Exactly once if the compute cell's value changed as a result of the set_value call.
     //   The value passed to the callback should be the final value of the compute cell after the
     //   set_value call.
-    pub fn add_callback<F: FnMut(T) -> () + 'a>(
+    pub fn add_callback<F: FnMut(T) + 'a>(
         &mut self,
         id: ComputeCellID,
         callback: F,
@@ -156,6 +224,19 @@ impl<'a, T: Copy + PartialEq> Reactor<'a, T> {
         self.data.get_mut(id.0).map(|cell| cell.add_callback(callback))
     }
 
+    // Subscribes to the changes of the specified cell, input or compute.
+    //
+    // Returns a stream yielding the new value of the cell each time it changes, or None if the
+    // cell doesn't exist.
+    //
+    // Values are sent under the same rules as callbacks: at most once per set_value call, and only
+    // when the value differs from the previous stable state. The stream ends when the Reactor is
+    // dropped; dropping the stream unsubscribes it.
+    #[cfg(feature = "stream")]
+    pub fn subscribe(&mut self, id: CellID) -> Option<impl Stream<Item = T>> {
+        self.data.get_mut(id.idx()).map(|cell| cell.subscribe())
+    }
+
     // Removes the specified callback, using an ID returned from add_callback.
     //
     // Returns an Err if either the cell or callback does not exist.
@@ -177,10 +258,30 @@ impl<'a, T: Copy + PartialEq> Reactor<'a, T> {
         compute_func(&inputs)
     }
 
-    fn propogate(&mut self, start_idx: usize) {
-        // FIXME: this allocation seems unnecessary, but I don't
-        // know how else to get around lifetime errors
-        let mut downstreams: BTreeSet<usize> = BTreeSet::from_iter(self.data[start_idx].downstreams.iter().cloned());
+    // Sets input cells, fires their callbacks and propagates, returning the inputs that changed.
+    fn apply(&mut self, values: &[(usize, T)]) -> Vec<Change<T>> {
+        let mut changes: Vec<Change<T>> = Vec::with_capacity(values.len());
+        for &(idx, value) in values {
+            match changes.iter_mut().find(|change| change.idx == idx) {
+                Some(change) => change.new = value,
+                None => changes.push(Change { idx, old: self.data[idx].value, new: value }),
+            }
+        }
+        changes.retain(|change| change.old != change.new);
+        for change in &changes {
+            let cell = &mut self.data[change.idx];
+            cell.value = change.new;
+            cell.run_callbacks();
+        }
+        self.propogate(changes.iter().map(|change| change.idx));
+        changes
+    }
+
+    fn propogate<I: IntoIterator<Item = usize>>(&mut self, start_idxs: I) {
+        let mut downstreams: BTreeSet<usize> = BTreeSet::new();
+        for idx in start_idxs {
+            downstreams.extend(&self.data[idx].downstreams);
+        }
         while let Some(&cell_id) = downstreams.iter().next() {
             let value = self.compute(cell_id);
             let cell = &mut self.data[cell_id];
@@ -200,9 +301,11 @@ struct Cell<'a, T> {
     downstreams: Vec<usize>,
     counter: usize,
     callbacks: Vec<Callback<'a, T>>,
+    #[cfg(feature = "stream")]
+    subscribers: Vec<UnboundedSender<T>>,
 }
 
-struct Callback<'a, T>(usize, Box<FnMut(T) -> ()+ 'a>);
+struct Callback<'a, T>(usize, Box<dyn FnMut(T) + 'a>);
 
 impl<'a, T: Copy> Cell<'a, T> {
     fn new(value: T) -> Self {
@@ -211,6 +314,8 @@ impl<'a, T: Copy> Cell<'a, T> {
             downstreams: Vec::new(),
             counter: 0,
             callbacks: Vec::new(),
+            #[cfg(feature = "stream")]
+            subscribers: Vec::new(),
         }
     }
 
@@ -218,7 +323,7 @@ impl<'a, T: Copy> Cell<'a, T> {
         self.downstreams.push(downstream);
     }
 
-    fn add_callback<F: FnMut(T) -> () + 'a>(&mut self, callback: F) -> CallbackID {
+    fn add_callback<F: FnMut(T) + 'a>(&mut self, callback: F) -> CallbackID {
         let id = self.counter;
         self.counter += 1;
         self.callbacks.push(Callback(id, Box::new(callback)));
@@ -234,15 +339,73 @@ impl<'a, T: Copy> Cell<'a, T> {
         }
     }
 
+    #[cfg(feature = "stream")]
+    fn subscribe(&mut self) -> impl Stream<Item = T> {
+        let (tx, rx) = unbounded();
+        self.subscribers.push(tx);
+        rx
+    }
+
     fn run_callbacks(&mut self) {
         for callback in self.callbacks.iter_mut() {
             callback.1(self.value);
         }
+        #[cfg(feature = "stream")]
+        {
+            let value = self.value;
+            self.subscribers.retain(|tx| tx.unbounded_send(value).is_ok());
+        }
     }
 }
 
 
+type ComputeFunc<'a, T> = Box<dyn Fn(&[T]) -> T + 'a>;
+
 struct ComputeInfo<'a, T> {
-    compute_func: Box<Fn(&[T]) -> T + 'a>,
+    compute_func: ComputeFunc<'a, T>,
     dependencies: Vec<usize>,
-}
\ No newline at end of file
+}
+
+#[derive(Clone, Copy)]
+struct Change<T> {
+    idx: usize,
+    old: T,
+    new: T,
+}
+
+struct History<T> {
+    revisions: Vec<Vec<Change<T>>>,
+    current: usize,
+}
+
+impl<T: Copy> History<T> {
+    fn new() -> Self {
+        History {
+            revisions: Vec::new(),
+            current: 0,
+        }
+    }
+
+    fn record(&mut self, changes: Vec<Change<T>>) {
+        if changes.is_empty() {
+            return;
+        }
+        self.revisions.truncate(self.current);
+        self.revisions.push(changes);
+        self.current += 1;
+    }
+
+    fn undo(&mut self) -> Option<Vec<Change<T>>> {
+        if self.current == 0 {
+            return None;
+        }
+        self.current -= 1;
+        Some(self.revisions[self.current].clone())
+    }
+
+    fn redo(&mut self) -> Option<Vec<Change<T>>> {
+        let changes = self.revisions.get(self.current)?.clone();
+        self.current += 1;
+        Some(changes)
+    }
+}
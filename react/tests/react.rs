@@ -417,3 +417,185 @@ fn test_adder_with_boolean_values() {
         );
     }
 }
+
+#[test]
+fn setting_several_values_fires_callbacks_once() {
+    let cb = CallbackRecorder::new();
+    let mut reactor = Reactor::new();
+    let a = reactor.create_input(1);
+    let b = reactor.create_input(2);
+    let output = reactor
+        .create_compute(&[CellID::Input(a), CellID::Input(b)], |v| v[0] + v[1])
+        .unwrap();
+    assert!(reactor
+        .add_callback(output, |v| cb.callback_called(v))
+        .is_some());
+
+    assert!(reactor.set_values(&[(a, 10), (b, 20)]));
+    cb.expect_to_have_been_called_with(30);
+    assert!(reactor.set_values(&[(a, 20), (b, 10)]));
+    cb.expect_not_to_have_been_called();
+}
+
+#[test]
+fn error_setting_several_values_with_a_nonexistent_input_cell() {
+    let mut dummy_reactor = Reactor::new();
+    let _ = dummy_reactor.create_input(1);
+    let dummy_cell = dummy_reactor.create_input(2);
+    let mut reactor = Reactor::new();
+    let input = reactor.create_input(1);
+    assert!(!reactor.set_values(&[(input, 5), (dummy_cell, 5)]));
+    assert_eq!(reactor.value(CellID::Input(input)), Some(1));
+}
+
+#[test]
+fn undo_and_redo_restore_inputs_and_compute_cells() {
+    let mut reactor = Reactor::with_history();
+    let input = reactor.create_input(1);
+    let output = reactor
+        .create_compute(&[CellID::Input(input)], |v| v[0] * 10)
+        .unwrap();
+    assert_eq!(reactor.revision(), 0);
+    assert!(reactor.set_value(input, 2));
+    assert!(reactor.set_value(input, 3));
+    assert_eq!(reactor.revision(), 2);
+
+    assert!(reactor.undo());
+    assert_eq!(reactor.revision(), 1);
+    assert_eq!(reactor.value(CellID::Input(input)), Some(2));
+    assert_eq!(reactor.value(CellID::Compute(output)), Some(20));
+    assert!(reactor.undo());
+    assert_eq!(reactor.value(CellID::Compute(output)), Some(10));
+    assert!(!reactor.undo());
+    assert_eq!(reactor.revision(), 0);
+
+    assert!(reactor.redo());
+    assert!(reactor.redo());
+    assert!(!reactor.redo());
+    assert_eq!(reactor.revision(), 2);
+    assert_eq!(reactor.value(CellID::Compute(output)), Some(30));
+}
+
+#[test]
+fn undo_fires_callbacks_once_per_batch() {
+    let cb = CallbackRecorder::new();
+    let mut reactor = Reactor::with_history();
+    let a = reactor.create_input(1);
+    let b = reactor.create_input(2);
+    let output = reactor
+        .create_compute(&[CellID::Input(a), CellID::Input(b)], |v| v[0] * v[1])
+        .unwrap();
+    assert!(reactor
+        .add_callback(output, |v| cb.callback_called(v))
+        .is_some());
+
+    assert!(reactor.set_values(&[(a, 3), (b, 4)]));
+    cb.expect_to_have_been_called_with(12);
+    assert_eq!(reactor.revision(), 1);
+
+    assert!(reactor.undo());
+    cb.expect_to_have_been_called_with(2);
+    assert!(reactor.redo());
+    cb.expect_to_have_been_called_with(12);
+}
+
+#[test]
+fn setting_a_value_after_undo_discards_redo() {
+    let mut reactor = Reactor::with_history();
+    let input = reactor.create_input(1);
+    assert!(reactor.set_value(input, 2));
+    assert!(reactor.set_value(input, 3));
+    assert!(reactor.undo());
+    assert!(reactor.set_value(input, 4));
+    assert!(!reactor.redo());
+    assert_eq!(reactor.revision(), 2);
+    assert!(reactor.undo());
+    assert_eq!(reactor.value(CellID::Input(input)), Some(2));
+}
+
+#[test]
+fn unchanged_values_do_not_create_revisions() {
+    let mut reactor = Reactor::with_history();
+    let input = reactor.create_input(1);
+    assert!(reactor.set_value(input, 1));
+    assert_eq!(reactor.revision(), 0);
+    assert!(!reactor.undo());
+}
+
+#[test]
+fn undo_without_history_does_nothing() {
+    let mut reactor = Reactor::new();
+    let input = reactor.create_input(1);
+    assert!(reactor.set_value(input, 2));
+    assert_eq!(reactor.revision(), 0);
+    assert!(!reactor.undo());
+    assert_eq!(reactor.value(CellID::Input(input)), Some(2));
+}
+
+#[test]
+#[cfg(feature = "stream")]
+fn subscriptions_yield_each_changed_value() {
+    use futures::executor::block_on_stream;
+
+    let mut reactor = Reactor::new();
+    let input = reactor.create_input(1);
+    let output = reactor
+        .create_compute(&[CellID::Input(input)], |v| v[0] + 1)
+        .unwrap();
+    let stream = reactor.subscribe(CellID::Compute(output)).unwrap();
+
+    assert!(reactor.set_value(input, 2));
+    assert!(reactor.set_value(input, 3));
+    drop(reactor);
+    assert_eq!(block_on_stream(stream).collect::<Vec<_>>(), vec![3, 4]);
+}
+
+#[test]
+#[cfg(feature = "stream")]
+fn subscriptions_only_yield_on_change() {
+    use futures::executor::block_on_stream;
+
+    let mut reactor = Reactor::new();
+    let input = reactor.create_input(1);
+    let output = reactor
+        .create_compute(
+            &[CellID::Input(input)],
+            |v| if v[0] < 3 { 111 } else { 222 },
+        )
+        .unwrap();
+    let inputs = reactor.subscribe(CellID::Input(input)).unwrap();
+    let outputs = reactor.subscribe(CellID::Compute(output)).unwrap();
+
+    assert!(reactor.set_value(input, 2));
+    assert!(reactor.set_value(input, 2));
+    assert!(reactor.set_value(input, 4));
+    drop(reactor);
+    assert_eq!(block_on_stream(inputs).collect::<Vec<_>>(), vec![2, 4]);
+    assert_eq!(block_on_stream(outputs).collect::<Vec<_>>(), vec![222]);
+}
+
+#[test]
+#[cfg(feature = "stream")]
+fn error_subscribing_to_nonexistent_cell() {
+    let mut dummy_reactor = Reactor::new();
+    let input = dummy_reactor.create_input(1);
+    assert!(Reactor::<i32>::new().subscribe(CellID::Input(input)).is_none());
+}
+
+#[test]
+#[cfg(feature = "stream")]
+fn dropped_subscriptions_do_not_interfere_with_callbacks() {
+    let cb = CallbackRecorder::new();
+    let mut reactor = Reactor::new();
+    let input = reactor.create_input(1);
+    let output = reactor
+        .create_compute(&[CellID::Input(input)], |v| v[0] + 1)
+        .unwrap();
+    assert!(reactor
+        .add_callback(output, |v| cb.callback_called(v))
+        .is_some());
+    drop(reactor.subscribe(CellID::Compute(output)).unwrap());
+
+    assert!(reactor.set_value(input, 2));
+    cb.expect_to_have_been_called_with(3);
+}
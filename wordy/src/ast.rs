@@ -0,0 +1,150 @@
+//! The arithmetic a question asks for.
+
+use crate::WordyError;
+#[cfg(feature = "rational")]
+use num_bigint::BigInt;
+#[cfg(feature = "rational")]
+use num_rational::BigRational;
+#[cfg(feature = "rational")]
+use num_traits::{One, Signed, ToPrimitive, Zero};
+use std::convert::TryInto;
+
+/// The operations questions may ask for, in the words of `plus`, `minus`, `multiplied by`,
+/// `divided by` and, with the `exponentials` feature, `raised to the Nth power`.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Operation {
+    Plus,
+    Minus,
+    Multiply,
+    Divide,
+    Power,
+}
+
+/// An integer of any size, as written in a question.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Integer {
+    /// Decimal digits, after a minus sign for negative integers.
+    digits: String,
+}
+
+impl Integer {
+    /// Reads decimal digits, after a minus sign for negative integers, or nothing else.
+    pub(crate) fn new(text: &str) -> Option<Integer> {
+        let digits = text.strip_prefix('-').unwrap_or(text);
+        if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+            return None;
+        }
+        Some(Integer { digits: text.to_string() })
+    }
+
+    fn to_i32(&self) -> Result<i32, WordyError> {
+        self.digits.parse().map_err(|_| WordyError::Overflow)
+    }
+
+    #[cfg(feature = "rational")]
+    fn to_big_int(&self) -> BigInt {
+        self.digits.parse().expect("integers are made of digits")
+    }
+}
+
+impl From<i32> for Integer {
+    fn from(value: i32) -> Self {
+        Integer { digits: value.to_string() }
+    }
+}
+
+/// An expression, built left to right or by the precedence of its operations.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum Expr {
+    Number(Integer),
+    Binary(Box<Expr>, Operation, Box<Expr>),
+}
+
+impl Expr {
+    /// Evaluates the expression in `i32`, failing rather than overflowing. Divisions truncate
+    /// toward zero.
+    pub fn evaluate(&self) -> Result<i32, WordyError> {
+        match self {
+            Expr::Number(integer) => integer.to_i32(),
+            Expr::Binary(left, operation, right) => {
+                let (x, y) = (left.evaluate()?, right.evaluate()?);
+                match operation {
+                    Operation::Plus => add(x, y),
+                    Operation::Minus => minus(x, y),
+                    Operation::Multiply => mult(x, y),
+                    Operation::Divide => div(x, y),
+                    Operation::Power => pow(x, y),
+                }
+            }
+        }
+    }
+
+    /// Evaluates the expression exactly, as a fraction of integers of any size, like `7/2`.
+    #[cfg(feature = "rational")]
+    pub fn evaluate_exact(&self) -> Result<BigRational, WordyError> {
+        match self {
+            Expr::Number(integer) => Ok(BigRational::from_integer(integer.to_big_int())),
+            Expr::Binary(left, Operation::Power, right) => exact_pow(left.evaluate_exact()?, right.evaluate_exact()?),
+            Expr::Binary(left, operation, right) => {
+                let (x, y) = (left.evaluate_exact()?, right.evaluate_exact()?);
+                match operation {
+                    Operation::Plus => Ok(x + y),
+                    Operation::Minus => Ok(x - y),
+                    Operation::Multiply => Ok(x * y),
+                    Operation::Divide if y.is_zero() => Err(WordyError::DivisionByZero),
+                    Operation::Divide => Ok(x / y),
+                    Operation::Power => unreachable!(),
+                }
+            }
+        }
+    }
+}
+
+/// The most bits the numerator or denominator of an exact power may take, about a hundred
+/// thousand decimal digits.
+#[cfg(feature = "rational")]
+const MAX_EXACT_POWER_BITS: u64 = 1 << 18;
+
+/// Raises `x` to the whole exponent `y`, failing rather than allocating powers beyond
+/// `MAX_EXACT_POWER_BITS`. Powers of 0, 1 and -1 never grow, whatever the exponent.
+#[cfg(feature = "rational")]
+fn exact_pow(x: BigRational, y: BigRational) -> Result<BigRational, WordyError> {
+    // Exponents are ordinals, so whole and not negative.
+    let y = y.to_integer();
+    if x.is_zero() || x.abs().is_one() {
+        // Past the 0th, their powers only depend on the parity of the exponent.
+        let y = if y.is_zero() { 0 } else if (y % 2u32).is_zero() { 2 } else { 1 };
+        return Ok(x.pow(y));
+    }
+    // `x` is neither 0 nor ±1, so the power grows by a bit at least with each step of the exponent.
+    let bits = x.numer().bits().max(x.denom().bits()) - 1;
+    match y.to_u64().and_then(|y| bits.checked_mul(y)) {
+        Some(total) if total <= MAX_EXACT_POWER_BITS => Ok(x.pow(y.to_i32().expect("bounded exponents fit"))),
+        _ => Err(WordyError::ExponentTooLarge),
+    }
+}
+
+fn add(x: i32, y: i32) -> Result<i32, WordyError> {
+    x.checked_add(y).ok_or(WordyError::Overflow)
+}
+
+fn minus(x: i32, y: i32) -> Result<i32, WordyError> {
+    x.checked_sub(y).ok_or(WordyError::Overflow)
+}
+
+fn mult(x: i32, y: i32) -> Result<i32, WordyError> {
+    x.checked_mul(y).ok_or(WordyError::Overflow)
+}
+
+fn div(x: i32, y: i32) -> Result<i32, WordyError> {
+    if y == 0 {
+        return Err(WordyError::DivisionByZero);
+    }
+    // Only `i32::MIN / -1` overflows.
+    x.checked_div(y).ok_or(WordyError::Overflow)
+}
+
+fn pow(x: i32, y: i32) -> Result<i32, WordyError> {
+    let y: u32 = y.try_into().map_err(|_| WordyError::Overflow)?;
+    x.checked_pow(y).ok_or(WordyError::Overflow)
+}
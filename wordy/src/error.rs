@@ -0,0 +1,33 @@
+use std::error;
+use std::fmt;
+
+/// Why a question has no answer.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum WordyError {
+    /// A word where an operation was expected, like `cubed`.
+    UnknownOperation(String),
+    /// Something other than `expected` at the byte offset `position` of the question, which is
+    /// its length when the question ends too early.
+    SyntaxError { position: usize, expected: String },
+    /// A number, or the result of an operation, too large for an `i32`.
+    Overflow,
+    DivisionByZero,
+    /// A power too large to compute exactly, like `2 raised to the 2000000000th power`.
+    ExponentTooLarge,
+}
+
+impl fmt::Display for WordyError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            WordyError::UnknownOperation(word) => write!(f, "unknown operation '{}'", word),
+            WordyError::SyntaxError { position, expected } => {
+                write!(f, "expected {} at position {}", expected, position)
+            }
+            WordyError::Overflow => write!(f, "number too large"),
+            WordyError::DivisionByZero => write!(f, "division by zero"),
+            WordyError::ExponentTooLarge => write!(f, "exponent too large for an exact answer"),
+        }
+    }
+}
+
+impl error::Error for WordyError {}
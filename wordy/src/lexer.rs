@@ -0,0 +1,69 @@
+//! Splitting a question into words, numbers, ordinals like `5th` and question marks.
+
+use crate::ast::Integer;
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub(crate) enum TokenKind {
+    Number(Integer),
+    /// The number of an ordinal, without its suffix.
+    Ordinal(Integer),
+    Word(String),
+    QuestionMark,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub(crate) struct Token {
+    pub(crate) kind: TokenKind,
+    /// The byte offset of the token in the question.
+    pub(crate) offset: usize,
+}
+
+/// Splits a question at whitespace, question marks being tokens of their own even when attached to
+/// a word.
+pub(crate) fn tokenize(question: &str) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    let mut start = None;
+    for (offset, c) in question.char_indices().chain(Some((question.len(), ' '))) {
+        if c.is_whitespace() || c == '?' {
+            if let Some(start) = start.take() {
+                tokens.push(Token { kind: classify(&question[start..offset]), offset: start });
+            }
+            if c == '?' {
+                tokens.push(Token { kind: TokenKind::QuestionMark, offset });
+            }
+        } else if start.is_none() {
+            start = Some(offset);
+        }
+    }
+    tokens
+}
+
+fn classify(word: &str) -> TokenKind {
+    let digits = word.strip_prefix('-').unwrap_or(word);
+    let numeral = digits.trim_end_matches(|c: char| !c.is_ascii_digit());
+    if numeral.is_empty() || !numeral.bytes().all(|b| b.is_ascii_digit()) {
+        return TokenKind::Word(word.to_string());
+    }
+    let suffix = &digits[numeral.len()..];
+    let number = if suffix.is_empty() {
+        Integer::new(word).map(TokenKind::Number)
+    } else if digits.len() == word.len() && suffix == ordinal_suffix(numeral) {
+        Integer::new(numeral).map(TokenKind::Ordinal)
+    } else {
+        None
+    };
+    number.unwrap_or_else(|| TokenKind::Word(word.to_string()))
+}
+
+/// The suffix of the ordinal of a number written in digits, like `st` for `21` or `th` for `11`.
+fn ordinal_suffix(numeral: &str) -> &'static str {
+    let bytes = numeral.as_bytes();
+    let tens = if bytes.len() > 1 { bytes[bytes.len() - 2] } else { b'0' };
+    match (tens, bytes[bytes.len() - 1]) {
+        (b'1', _) => "th",
+        (_, b'1') => "st",
+        (_, b'2') => "nd",
+        (_, b'3') => "rd",
+        _ => "th",
+    }
+}
@@ -1,104 +1,54 @@
-use std::collections::VecDeque;
-use regex::Regex;
-
-fn add(x: i32, y: i32) -> i32 {
-    x + y
+//! Answers math word problems like `What is 5 plus 13?`.
+//!
+//! Questions are split into tokens, parsed into an expression, then evaluated. Being read aloud,
+//! they are evaluated left to right by default, ignoring the usual order of operations, so that
+//! `What is 3 plus 2 multiplied by 3?` is 15. `Mode::Precedence` makes it 9.
+//!
+//! Powers, like `What is 2 raised to the 5th power?`, are only understood with the `exponentials`
+//! feature.
+//!
+//! Answers are `i32`, and questions whose answer does not fit fail with `WordyError::Overflow`.
+//! The `rational` feature adds `solve_exact`, answering with fractions of integers of any size.
+
+mod ast;
+mod error;
+mod lexer;
+mod parser;
+
+pub use crate::ast::{Expr, Integer, Operation};
+pub use crate::error::WordyError;
+#[cfg(feature = "rational")]
+pub use num_rational::BigRational;
+
+/// How the operations of a question are grouped.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub enum Mode {
+    /// Each operation applies to the result of the ones before it.
+    #[default]
+    LeftToRight,
+    /// Powers come first, then multiplications and divisions, then additions and subtractions,
+    /// each from the left.
+    Precedence,
 }
 
-fn minus(x: i32, y: i32) -> i32 {
-    x - y
+/// Parses a question into the expression it asks for.
+pub fn parse(command: &str, mode: Mode) -> Result<Expr, WordyError> {
+    parser::Parser::new(lexer::tokenize(command), command.len()).question(mode)
 }
 
-fn mult(x: i32, y: i32) -> i32 {
-    x * y
+/// Answers a question with its operations grouped by `mode`, or tells why it cannot.
+pub fn solve(command: &str, mode: Mode) -> Result<i32, WordyError> {
+    parse(command, mode)?.evaluate()
 }
 
-fn div(x: i32, y: i32) -> i32 {
-    x / y
-}
-use std::convert::TryInto;
-fn pow(x: i32, y: i32) -> i32 {
-    (x as u32).pow(y.try_into().unwrap()) as i32
+/// Answers a question exactly, with integers of any size and fractions where divisions are not
+/// whole, so that `What is 7 divided by 2?` is `7/2`.
+#[cfg(feature = "rational")]
+pub fn solve_exact(command: &str, mode: Mode) -> Result<BigRational, WordyError> {
+    parse(command, mode)?.evaluate_exact()
 }
 
+/// Answers a question read left to right.
 pub fn answer(command: &str) -> Option<i32> {
-    let mut ops: VecDeque<fn(i32, i32) -> i32> = VecDeque::new();
-    let mut vals: VecDeque<i32> = VecDeque::new();
-
-    let re = Regex::new(r"^What is (.*)\?$").unwrap();
-    let caps = re.captures(command);
-
-    let mut s = "";
-    if caps.is_some() {
-        s = caps.unwrap().get(1).unwrap().as_str();
-    } else {
-        return None;
-    }
-
-    dbg!(s);
-    let mut expect = "val";
-    for token in s.split_whitespace() {
-        dbg!(token);
-        match expect {
-            "val" => {
-                match token.parse() {
-                    Ok(v) => {
-                        vals.push_back(v);
-                        expect = "op";
-                    }
-                    _ => { return None; }
-                }
-            }
-            "op" => {
-                match token {
-                    "plus" => {
-                        ops.push_back(add);
-                        expect = "val";
-                    }
-                    "minus" => {
-                        ops.push_back(minus);
-                        expect = "val";
-                    }
-                    "multiplied" => {
-                        ops.push_back(mult);
-                        expect = "by";
-                    }
-                    "divided" => {
-                        ops.push_back(div);
-                        expect = "by";
-                    }
-                    "raised" => {
-                        ops.push_back(pow);
-                        expect = "raised";
-                    }
-                    _ => { return None; }
-                }
-            }
-            "by" => {
-                if token != "by" { return None; };
-                expect = "val";
-            }
-            "raised" => { assert_eq!(token, "to"); expect = "to"; }
-            "to" => { assert_eq!(token, "the"); expect = "the"; }
-            "the" => {
-                let v: Vec<i32> = token.chars().take(1).map(|v| v.to_string().parse().unwrap()).collect();
-                vals.push_back(v[0]);
-                expect = "power";
-            }
-            "power" => {}
-            _ => unreachable!()
-        }
-    };
-    if ops.len() != vals.len() - 1 {
-        None
-    } else if vals.len() == 1 {
-        Some(vals.pop_front().unwrap())
-    } else {
-        for op in ops.iter() {
-            let x = vals.pop_front().unwrap();
-            let y = vals.pop_front().unwrap();
-            vals.push_front(op(x, y));
-        }
-        Some(vals.pop_front().unwrap())
-    }
+    solve(command, Mode::LeftToRight).ok()
 }
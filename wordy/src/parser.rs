@@ -0,0 +1,142 @@
+//! A recursive-descent parser for questions like `What is 3 plus 2 multiplied by 3?`.
+//!
+//! question = "What" "is" expression "?"
+//! expression = operand { operation operand }            (left to right)
+//! expression = product { ("plus" | "minus") product }   (by precedence)
+//! product = power { ("multiplied" | "divided") "by" power }
+//! power = number { "raised" "to" "the" ordinal "power" }
+
+use crate::ast::Expr;
+use crate::ast::Operation::{self, *};
+use crate::lexer::{Token, TokenKind};
+use crate::{Mode, WordyError};
+
+pub(crate) struct Parser {
+    tokens: Vec<Token>,
+    position: usize,
+    /// The length of the question, where errors about its end are reported.
+    end: usize,
+}
+
+impl Parser {
+    pub(crate) fn new(tokens: Vec<Token>, end: usize) -> Parser {
+        Parser { tokens, position: 0, end }
+    }
+
+    pub(crate) fn question(&mut self, mode: Mode) -> Result<Expr, WordyError> {
+        self.word("What")?;
+        self.word("is")?;
+        let expr = match mode {
+            Mode::LeftToRight => self.binary(&[Plus, Minus, Multiply, Divide, Power], Parser::number)?,
+            Mode::Precedence => self.sum()?,
+        };
+        // Every operation has been taken by now, so other words are unknown ones.
+        match self.tokens.get(self.position).map(|token| &token.kind) {
+            Some(TokenKind::Word(word)) => return Err(WordyError::UnknownOperation(word.clone())),
+            _ => self.next_if(|kind| *kind == TokenKind::QuestionMark, "an operation or '?'")?,
+        };
+        match self.tokens.get(self.position) {
+            Some(token) => Err(WordyError::SyntaxError {
+                position: token.offset,
+                expected: "the end of the question".to_string(),
+            }),
+            None => Ok(expr),
+        }
+    }
+
+    fn sum(&mut self) -> Result<Expr, WordyError> {
+        self.binary(&[Plus, Minus], Parser::product)
+    }
+
+    fn product(&mut self) -> Result<Expr, WordyError> {
+        self.binary(&[Multiply, Divide], Parser::power)
+    }
+
+    fn power(&mut self) -> Result<Expr, WordyError> {
+        self.binary(&[Power], Parser::number)
+    }
+
+    /// Parses operands joined by any of `operations`, folding them from the left. The exponent of
+    /// a power is always an ordinal.
+    fn binary(
+        &mut self,
+        operations: &[Operation],
+        operand: fn(&mut Parser) -> Result<Expr, WordyError>,
+    ) -> Result<Expr, WordyError> {
+        let mut expr = operand(self)?;
+        while let Some(operation) = self.operation(operations)? {
+            let right = match operation {
+                Power => self.exponent()?,
+                _ => operand(self)?,
+            };
+            expr = Expr::Binary(Box::new(expr), operation, Box::new(right));
+        }
+        Ok(expr)
+    }
+
+    /// Takes the words of one of `operations` up to its right operand, if it comes next.
+    fn operation(&mut self, operations: &[Operation]) -> Result<Option<Operation>, WordyError> {
+        let operation = match self.tokens.get(self.position).map(|token| &token.kind) {
+            Some(TokenKind::Word(word)) => match word.as_str() {
+                "plus" => Plus,
+                "minus" => Minus,
+                "multiplied" => Multiply,
+                "divided" => Divide,
+                #[cfg(feature = "exponentials")]
+                "raised" => Power,
+                _ => return Ok(None),
+            },
+            _ => return Ok(None),
+        };
+        if !operations.contains(&operation) {
+            return Ok(None);
+        }
+        self.position += 1;
+        match operation {
+            Multiply | Divide => self.word("by")?,
+            Power => {
+                self.word("to")?;
+                self.word("the")?;
+            }
+            _ => {}
+        }
+        Ok(Some(operation))
+    }
+
+    fn number(&mut self) -> Result<Expr, WordyError> {
+        match self.next_if(|kind| matches!(kind, TokenKind::Number(_)), "a number")? {
+            TokenKind::Number(value) => Ok(Expr::Number(value)),
+            _ => unreachable!(),
+        }
+    }
+
+    /// The `Nth power` ending `raised to the Nth power`.
+    fn exponent(&mut self) -> Result<Expr, WordyError> {
+        let ordinal = self.next_if(|kind| matches!(kind, TokenKind::Ordinal(_)), "an ordinal like '2nd'")?;
+        let exponent = match ordinal {
+            TokenKind::Ordinal(value) => value,
+            _ => unreachable!(),
+        };
+        self.word("power")?;
+        Ok(Expr::Number(exponent))
+    }
+
+    fn word(&mut self, expected: &str) -> Result<(), WordyError> {
+        let accept = |kind: &TokenKind| matches!(kind, TokenKind::Word(word) if word == expected);
+        self.next_if(accept, &format!("'{}'", expected)).map(|_| ())
+    }
+
+    /// Takes the next token if `accept`s it, and otherwise fails expecting `expected`.
+    fn next_if(&mut self, accept: impl Fn(&TokenKind) -> bool, expected: &str) -> Result<TokenKind, WordyError> {
+        match self.tokens.get(self.position) {
+            Some(token) if accept(&token.kind) => {
+                self.position += 1;
+                Ok(token.kind.clone())
+            }
+            token => Err(WordyError::SyntaxError {
+                position: token.map_or(self.end, |token| token.offset),
+                expected: expected.to_string(),
+            }),
+        }
+    }
+}
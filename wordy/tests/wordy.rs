@@ -1,4 +1,6 @@
-use wordy::answer;
+use wordy::{answer, parse, solve, Expr, Integer, Mode, Operation, WordyError};
+#[cfg(feature = "rational")]
+use wordy::solve_exact;
 
 #[test]
 fn just_a_number() {
@@ -153,3 +155,269 @@ fn addition_and_exponential() {
     let command = "What is 1 plus 2 raised to the 2nd power?";
     assert_eq!(Some(9), answer(command));
 }
+
+#[test]
+fn left_to_right_is_the_default_mode() {
+    let command = "What is 3 plus 2 multiplied by 3?";
+    assert_eq!(Mode::default(), Mode::LeftToRight);
+    assert_eq!(Ok(15), solve(command, Mode::LeftToRight));
+}
+
+#[test]
+fn multiplication_before_addition_with_precedence() {
+    let command = "What is 3 plus 2 multiplied by 3?";
+    assert_eq!(Ok(9), solve(command, Mode::Precedence));
+}
+
+#[test]
+fn same_precedence_from_the_left() {
+    let command = "What is 20 minus 4 minus 13 plus 1?";
+    assert_eq!(Ok(4), solve(command, Mode::Precedence));
+    let command = "What is -12 divided by 2 multiplied by -3?";
+    assert_eq!(Ok(18), solve(command, Mode::Precedence));
+}
+
+#[test]
+fn mixed_precedence() {
+    let command = "What is 1 plus 6 divided by 2 minus 2 multiplied by 3?";
+    assert_eq!(Ok(-2), solve(command, Mode::Precedence));
+    assert_eq!(Ok(3), solve(command, Mode::LeftToRight));
+}
+
+#[test]
+fn syntax_errors_with_precedence() {
+    let commands = ["What is 1 plus?", "What is 1 plus plus 2?", "What is 1 2 plus?", "What is?", "What is 52 cubed?"];
+    for command in commands.iter() {
+        assert!(solve(command, Mode::Precedence).is_err(), "{}", command);
+    }
+}
+
+#[test]
+fn parse_trees() {
+    use Expr::Binary;
+    let binary = |left, operation, right| Binary(Box::new(left), operation, Box::new(right));
+    let number = |value: i32| Expr::Number(value.into());
+    let command = "What is 3 plus 2 multiplied by 3?";
+    assert_eq!(
+        parse(command, Mode::LeftToRight),
+        Ok(binary(binary(number(3), Operation::Plus, number(2)), Operation::Multiply, number(3)))
+    );
+    assert_eq!(
+        parse(command, Mode::Precedence),
+        Ok(binary(number(3), Operation::Plus, binary(number(2), Operation::Multiply, number(3))))
+    );
+    assert_eq!(parse("What is 5?", Mode::Precedence), Ok(number(5)));
+}
+
+#[test]
+fn expressions_built_by_hand() {
+    let number = |value: i32| Box::new(Expr::Number(Integer::from(value)));
+    let sum = Expr::Binary(number(i32::MIN), Operation::Plus, number(7));
+    assert_eq!(sum.evaluate(), Ok(i32::MIN + 7));
+}
+
+#[test]
+fn question_mark_may_stand_apart() {
+    let command = "What is 1 plus 1 ?";
+    assert_eq!(Some(2), answer(command));
+}
+
+#[test]
+fn reject_text_after_the_question_mark() {
+    let command = "What is 1 plus 1? 2";
+    assert_eq!(None, answer(command));
+}
+
+#[test]
+fn reject_missing_question_mark() {
+    let command = "What is 1 plus 1";
+    assert_eq!(None, answer(command));
+}
+
+#[test]
+fn reject_missing_by() {
+    let command = "What is 2 multiplied 3?";
+    assert_eq!(None, answer(command));
+}
+
+#[test]
+fn reject_numbers_too_large() {
+    let command = "What is 2147483648 minus 1?";
+    assert_eq!(None, answer(command));
+}
+
+#[test]
+#[cfg(feature = "exponentials")]
+fn exponential_before_addition_with_precedence() {
+    let command = "What is 1 plus 2 raised to the 2nd power?";
+    assert_eq!(Ok(5), solve(command, Mode::Precedence));
+}
+
+#[test]
+#[cfg(feature = "exponentials")]
+fn exponential_before_multiplication_with_precedence() {
+    let command = "What is 3 multiplied by 2 raised to the 3rd power raised to the 1st power?";
+    assert_eq!(Ok(24), solve(command, Mode::Precedence));
+    assert_eq!(Ok(216), solve(command, Mode::LeftToRight));
+}
+
+#[test]
+#[cfg(feature = "exponentials")]
+fn reject_wrong_ordinals() {
+    let commands = [
+        "What is 2 raised to the 5nd power?",
+        "What is 2 raised to the 5 power?",
+        "What is 2 raised to the 11st power?",
+    ];
+    for command in commands.iter() {
+        assert_eq!(None, answer(command), "{}", command);
+    }
+    let command = "What is 2 raised to the 12th power?";
+    assert_eq!(Some(4096), answer(command));
+}
+
+fn syntax_error(position: usize, expected: &str) -> Result<i32, WordyError> {
+    Err(WordyError::SyntaxError { position, expected: expected.to_string() })
+}
+
+#[test]
+fn error_for_unknown_operation() {
+    let command = "What is 52 cubed?";
+    assert_eq!(Err(WordyError::UnknownOperation("cubed".to_string())), solve(command, Mode::LeftToRight));
+    assert_eq!(Err(WordyError::UnknownOperation("cubed".to_string())), solve(command, Mode::Precedence));
+}
+
+#[test]
+fn error_for_missing_operand() {
+    assert_eq!(syntax_error(14, "a number"), solve("What is 1 plus?", Mode::LeftToRight));
+    assert_eq!(syntax_error(7, "a number"), solve("What is?", Mode::LeftToRight));
+    assert_eq!(syntax_error(15, "a number"), solve("What is 1 plus plus 2?", Mode::Precedence));
+    assert_eq!(syntax_error(8, "a number"), solve("What is plus 1 2?", Mode::LeftToRight));
+}
+
+#[test]
+fn error_for_misplaced_words() {
+    assert_eq!(syntax_error(0, "'What'"), solve("Who is the President of the United States?", Mode::LeftToRight));
+    assert_eq!(syntax_error(10, "an operation or '?'"), solve("What is 1 2 plus?", Mode::LeftToRight));
+    assert_eq!(syntax_error(21, "'by'"), solve("What is 2 multiplied 3?", Mode::LeftToRight));
+    assert_eq!(syntax_error(16, "an operation or '?'"), solve("What is 1 plus 1", Mode::LeftToRight));
+    assert_eq!(syntax_error(18, "the end of the question"), solve("What is 1 plus 1? 2", Mode::LeftToRight));
+}
+
+#[test]
+fn error_for_numbers_too_large() {
+    assert_eq!(Err(WordyError::Overflow), solve("What is 2147483648 minus 1?", Mode::LeftToRight));
+    assert_eq!(Ok(i32::MIN), solve("What is -2147483648?", Mode::LeftToRight));
+}
+
+#[test]
+fn error_for_division_by_zero() {
+    assert_eq!(Err(WordyError::DivisionByZero), solve("What is 1 divided by 0?", Mode::LeftToRight));
+    assert_eq!(Err(WordyError::DivisionByZero), solve("What is 1 plus 2 divided by 0?", Mode::Precedence));
+    assert_eq!(None, answer("What is 1 divided by 0?"));
+}
+
+#[test]
+fn error_messages() {
+    assert_eq!(WordyError::UnknownOperation("cubed".to_string()).to_string(), "unknown operation 'cubed'");
+    let error = solve("What is 1 plus?", Mode::LeftToRight).unwrap_err();
+    assert_eq!(error.to_string(), "expected a number at position 14");
+    assert_eq!(WordyError::DivisionByZero.to_string(), "division by zero");
+}
+
+#[test]
+#[cfg(feature = "exponentials")]
+fn error_for_missing_ordinal() {
+    let command = "What is 2 raised to the 5 power?";
+    assert_eq!(syntax_error(24, "an ordinal like '2nd'"), solve(command, Mode::LeftToRight));
+}
+
+#[test]
+fn error_for_results_too_large() {
+    let commands = [
+        "What is 2147483647 plus 1?",
+        "What is -2147483648 minus 1?",
+        "What is 65536 multiplied by 32768?",
+        "What is -2147483648 divided by -1?",
+        "What is -2147483648 multiplied by -1?",
+    ];
+    for command in commands.iter() {
+        assert_eq!(Err(WordyError::Overflow), solve(command, Mode::LeftToRight), "{}", command);
+    }
+    assert_eq!(Ok(i32::MAX), solve("What is 2147483646 plus 1?", Mode::LeftToRight));
+    assert_eq!(Ok(-2147483647), solve("What is -2147483648 divided by 1 plus 1?", Mode::LeftToRight));
+}
+
+#[test]
+fn division_truncates_toward_zero() {
+    assert_eq!(Some(3), answer("What is 7 divided by 2?"));
+    assert_eq!(Some(-3), answer("What is -7 divided by 2?"));
+}
+
+#[test]
+#[cfg(not(feature = "exponentials"))]
+fn exponentials_need_their_feature() {
+    let command = "What is 2 raised to the 5th power?";
+    assert_eq!(Err(WordyError::UnknownOperation("raised".to_string())), solve(command, Mode::LeftToRight));
+}
+
+#[test]
+#[cfg(feature = "exponentials")]
+fn exponentials_of_negative_numbers() {
+    assert_eq!(Some(-8), answer("What is -2 raised to the 3rd power?"));
+    assert_eq!(Some(16), answer("What is -2 raised to the 4th power?"));
+    assert_eq!(Some(1), answer("What is 5 raised to the 0th power?"));
+}
+
+#[test]
+#[cfg(feature = "exponentials")]
+fn error_for_exponentials_too_large() {
+    assert_eq!(Some(1 << 30), answer("What is 2 raised to the 30th power?"));
+    assert_eq!(Err(WordyError::Overflow), solve("What is 2 raised to the 31st power?", Mode::LeftToRight));
+    assert_eq!(Some(i32::MIN), answer("What is -2 raised to the 31st power?"));
+}
+
+#[test]
+#[cfg(feature = "rational")]
+fn exact_division() {
+    let exact = |command: &str, mode: Mode| solve_exact(command, mode).map(|answer| answer.to_string());
+    assert_eq!(Ok("7/2".to_string()), exact("What is 7 divided by 2?", Mode::LeftToRight));
+    assert_eq!(Ok("-7/2".to_string()), exact("What is 7 divided by -2?", Mode::LeftToRight));
+    assert_eq!(Ok("3".to_string()), exact("What is 6 divided by 2?", Mode::LeftToRight));
+    assert_eq!(Ok("5/6".to_string()), exact("What is 1 divided by 2 plus 1 divided by 3?", Mode::Precedence));
+    assert_eq!(Ok("7/4".to_string()), exact("What is 1 plus 6 divided by 2 divided by 2?", Mode::LeftToRight));
+    assert_eq!(Ok("-3/2".to_string()), exact("What is 1 divided by 2 minus 2?", Mode::Precedence));
+    assert_eq!(Err(WordyError::DivisionByZero), exact("What is 1 divided by 0?", Mode::LeftToRight));
+}
+
+#[test]
+#[cfg(feature = "rational")]
+fn exact_answers_do_not_overflow() {
+    let exact = |command: &str| solve_exact(command, Mode::LeftToRight).unwrap().to_string();
+    assert_eq!("2147483648", exact("What is 2147483647 plus 1?"));
+    assert_eq!("4611686014132420609", exact("What is 2147483647 multiplied by 2147483647?"));
+    assert_eq!("2147483648", exact("What is -2147483648 divided by -1?"));
+    assert_eq!("3000000001", exact("What is 3000000000 plus 1?"));
+    assert_eq!(Err(WordyError::Overflow), solve("What is 3000000000 plus 1?", Mode::LeftToRight));
+}
+
+#[test]
+#[cfg(all(feature = "rational", feature = "exponentials"))]
+fn exact_exponentials() {
+    let exact = |command: &str| solve_exact(command, Mode::LeftToRight).unwrap().to_string();
+    assert_eq!("1267650600228229401496703205376", exact("What is 2 raised to the 100th power?"));
+    assert_eq!("1/8", exact("What is 1 divided by 2 raised to the 3rd power?"));
+    assert_eq!("-1", exact("What is -1 raised to the 2000000001st power?"));
+    assert_eq!("0", exact("What is 0 raised to the 99999999999999999999th power?"));
+}
+
+#[test]
+#[cfg(all(feature = "rational", feature = "exponentials"))]
+fn error_for_exact_exponentials_too_large() {
+    let exact = |command: &str| solve_exact(command, Mode::LeftToRight);
+    assert_eq!(Err(WordyError::ExponentTooLarge), exact("What is 2 raised to the 2000000000th power?"));
+    assert_eq!(Err(WordyError::ExponentTooLarge), exact("What is 1 divided by 3 raised to the 99999999999th power?"));
+    let tower = "What is 2 raised to the 1000th power raised to the 1000th power?";
+    assert_eq!(Err(WordyError::ExponentTooLarge), exact(tower));
+    assert!(exact("What is 2 raised to the 100000th power?").is_ok());
+}